@@ -1,8 +1,25 @@
+use crate::allocation::{allocation_bar_charts, allocation_pie};
 use crate::asset::{AssetTrait, AssetType};
+use crate::asset_table::AssetTable;
+use crate::chart_image::{encode_png, series_to_svg};
+use crate::csv_import_dialog::CsvImportDialog;
+use crate::export::{group_series, plot_series, save_file, ExportFormat, PlotSeries};
+use crate::format::{format_amount, format_compact};
+use crate::goal::{Goal, GoalStatus};
+use crate::history::History;
+use crate::i18n::{set_locale, tr, Locale};
 use crate::models::Asset;
-use crate::plot_utils::{create_plot_line, create_portfolio_plot_line};
-use crate::{Cash, Loan, Portfolio, RealEstate, Tradable};
-use chrono::{Datelike, NaiveDate, TimeZone, Utc};
+use crate::plot_utils::{
+    create_series_plot_line, create_stacked_area_bands, create_unvested_plot_line,
+    format_timestamp, hover_text,
+};
+use crate::report::{annual_report, ReportOptions};
+use crate::statement_import_dialog::StatementImportDialog;
+use crate::widgets::{date_edit, dropped_file_text, group_edit, payout_target_edit, tags_edit};
+use crate::{
+    Bond, Cash, CreditCard, Depreciating, EquityGrant, Loan, Portfolio, RealEstate, Tradable,
+};
+use chrono::{Datelike, NaiveDate, Utc};
 use eframe::egui;
 use egui_plot::{Legend, Plot};
 use uuid::Uuid;
@@ -13,6 +30,44 @@ pub struct ApplicationSettings {
     pub interval_days: i64,
     pub end_date: (i32, u32),
     pub currency: String,
+    #[serde(default)]
+    pub chart_mode: ChartMode,
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub plot_by_group: bool, // one line per group instead of per asset
+    #[serde(default)]
+    pub asset_view: AssetView,
+    #[serde(default)]
+    pub asset_table: AssetTable, // sort order of the table view
+    #[serde(default)]
+    pub templates: Vec<Asset>, // saved assets offered when adding new ones
+}
+
+/// How the central chart shows the portfolio.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum ChartMode {
+    #[default]
+    Lines, // each asset as its own line
+    StackedArea, // assets stacked above zero, liabilities below, with the net worth on top
+    AllocationPie,
+    AllocationBar,
+}
+
+/// How the side panel lists the assets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum AssetView {
+    #[default]
+    List, // a collapsible editor per asset
+    Table, // one sortable row per asset
+}
+
+/// What was asked of an asset in the side panel list.
+enum AssetAction {
+    Delete(Uuid),
+    Duplicate(Uuid),
+    SaveTemplate(Uuid),
+    Move { dragged: Uuid, target: Uuid },
 }
 
 impl Default for ApplicationSettings {
@@ -22,9 +77,31 @@ impl Default for ApplicationSettings {
             interval_days: 45,
             end_date: (Utc::now().date_naive().year() + 30, 1),
             currency: CURRENCY_SYMBOLS[0].to_string(),
+            chart_mode: ChartMode::default(),
+            locale: Locale::default(),
+            plot_by_group: false,
+            asset_view: AssetView::default(),
+            asset_table: AssetTable::default(),
+            templates: Vec::new(),
         }
     }
 }
+impl ApplicationSettings {
+    /// First day of the configured end month.
+    pub fn end_date(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.end_date.0, self.end_date.1, 1).unwrap()
+    }
+}
+
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
+/// How long the undo toast is shown after a deletion.
+const UNDO_TOAST_SECONDS: f64 = 6.0;
+
 const CURRENCY_SYMBOLS: [&str; 18] = [
     "USD", "GBP", "EUR", "SEK", "JPY", "AUD", "CAD", "CHF", "CNY", "HKD", "NZD", "SGD", "MYR",
     "THB", "PHP", "IDR", "KRW", "CZK",
@@ -35,6 +112,35 @@ pub struct WealthTrackerApp {
     portfolio: Portfolio,
     selected_asset_type: AssetType,
     application_settings: ApplicationSettings,
+    #[serde(skip)]
+    csv_import_dialog: CsvImportDialog,
+    #[serde(skip)]
+    statement_import_dialog: StatementImportDialog,
+    #[serde(skip)]
+    export_status: Option<String>,
+    #[serde(skip)]
+    plot_rect: Option<egui::Rect>, // where the plot was last drawn, for PNG export
+    #[serde(skip)]
+    png_export_pending: bool,
+    #[serde(skip)]
+    report_date: Option<NaiveDate>, // today unless another date is chosen
+    #[serde(skip)]
+    allocation_date: Option<NaiveDate>, // today unless another date is chosen
+    #[serde(skip)]
+    tag_filter: Option<String>, // only assets with this tag are listed
+    #[serde(skip)]
+    history: History,
+    #[serde(skip)]
+    recorded: bool, // no input since the history last recorded the portfolio
+    #[serde(skip)]
+    deleted: Option<Deleted>,
+}
+
+/// The last deletion, for the undo toast.
+struct Deleted {
+    name: String,
+    at: f64,        // input time of the deletion
+    recorded: bool, // the deletion is the top undo step
 }
 
 impl Default for WealthTrackerApp {
@@ -88,6 +194,17 @@ impl Default for WealthTrackerApp {
             portfolio,
             selected_asset_type: AssetType::RealEstate,
             application_settings: ApplicationSettings::default(),
+            csv_import_dialog: CsvImportDialog::default(),
+            statement_import_dialog: StatementImportDialog::default(),
+            export_status: None,
+            plot_rect: None,
+            png_export_pending: false,
+            report_date: None,
+            allocation_date: None,
+            tag_filter: None,
+            history: History::default(),
+            recorded: false,
+            deleted: None,
         }
     }
 }
@@ -100,6 +217,103 @@ impl WealthTrackerApp {
         }
         Default::default()
     }
+
+    /// Offers to undo the last deletion for a few seconds after it was made.
+    fn undo_toast(&mut self, ctx: &egui::Context) {
+        let Some(Deleted { name, at, .. }) = &self.deleted else {
+            return;
+        };
+        let remaining = UNDO_TOAST_SECONDS - (ctx.input(|input| input.time) - at);
+        if remaining <= 0.0 {
+            self.deleted = None;
+            return;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("undo_toast"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -16.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} {}", tr("Deleted"), name));
+                        undo = ui.button(tr("Undo")).clicked();
+                    });
+                });
+            });
+        if undo {
+            // Only the deletion itself is undone: the toast is gone once anything else is
+            // recorded after it.
+            self.record_history();
+            if self.deleted.take().is_some_and(|deleted| deleted.recorded) {
+                self.history.undo(&mut self.portfolio);
+            }
+        }
+    }
+
+    /// Whether the chart shows one line per group. The setting is kept, but has no effect while
+    /// there are no groups and its checkbox is hidden.
+    fn plot_by_group(&self) -> bool {
+        self.application_settings.plot_by_group
+            && self.application_settings.chart_mode == ChartMode::Lines
+            && !self.portfolio.groups().is_empty()
+    }
+
+    /// Records the portfolio in the history, and notes whether the recorded step is the last
+    /// deletion or a later change.
+    fn record_history(&mut self) {
+        self.recorded = true;
+        if self.history.record(&self.portfolio) {
+            match &mut self.deleted {
+                Some(deleted) if !deleted.recorded => deleted.recorded = true,
+                _ => self.deleted = None,
+            }
+        }
+    }
+
+    fn undo(&mut self) {
+        self.history.undo(&mut self.portfolio);
+        self.deleted = None;
+    }
+
+    fn redo(&mut self) {
+        self.history.redo(&mut self.portfolio);
+        self.deleted = None;
+    }
+
+    /// A menu of the saved templates. Picking one adds a new asset like it; the delete button
+    /// next to it forgets the template.
+    fn templates_ui(&mut self, ui: &mut egui::Ui) {
+        let mut to_add = None;
+        let mut to_remove = None;
+        egui::ComboBox::from_id_salt("asset_templates")
+            .selected_text(tr("From template"))
+            .show_ui(ui, |ui| {
+                for (index, template) in self.application_settings.templates.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let label = egui::RichText::new(template.name()).color(template.color());
+                        if ui.selectable_label(false, label).clicked() {
+                            to_add = Some(index);
+                        }
+                        if ui
+                            .small_button(egui_material_icons::icons::ICON_DELETE)
+                            .on_hover_text(tr("Delete template"))
+                            .clicked()
+                        {
+                            to_remove = Some(index);
+                        }
+                    });
+                }
+            });
+        if let Some(index) = to_add {
+            // Made from the template again, so that it is acquired on the day it is added.
+            let asset = self.application_settings.templates[index].to_template();
+            self.portfolio.add_asset(asset);
+        }
+        if let Some(index) = to_remove {
+            self.application_settings.templates.remove(index);
+        }
+    }
 }
 
 impl eframe::App for WealthTrackerApp {
@@ -107,71 +321,174 @@ impl eframe::App for WealthTrackerApp {
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        set_locale(self.application_settings.locale);
+
+        // Undo and redo, unless a text field is using the keys itself.
+        if !ctx.wants_keyboard_input() {
+            let (redo, undo) = ctx.input_mut(|input| {
+                (
+                    input.consume_shortcut(&REDO_SHORTCUT),
+                    input.consume_shortcut(&UNDO_SHORTCUT),
+                )
+            });
+            if redo {
+                self.redo();
+            } else if undo {
+                self.undo();
+            }
+        }
+
         egui::SidePanel::left("left").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.history.can_undo(),
+                        egui::Button::new(egui_material_icons::icons::ICON_UNDO),
+                    )
+                    .on_hover_text(tr("Undo (Ctrl+Z)"))
+                    .clicked()
+                {
+                    self.undo();
+                }
+                if ui
+                    .add_enabled(
+                        self.history.can_redo(),
+                        egui::Button::new(egui_material_icons::icons::ICON_REDO),
+                    )
+                    .on_hover_text(tr("Redo (Ctrl+Shift+Z)"))
+                    .clicked()
+                {
+                    self.redo();
+                }
+            });
             ui.group(|ui| {
-                ui.heading("Add Assets");
-                egui::ComboBox::from_label("Select asset type")
-                    .selected_text(format!("{:?}", self.selected_asset_type))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut self.selected_asset_type,
-                            AssetType::RealEstate,
-                            "Real Estate",
-                        );
-                        ui.selectable_value(&mut self.selected_asset_type, AssetType::Loan, "Loan");
-                        ui.selectable_value(
-                            &mut self.selected_asset_type,
-                            AssetType::Tradable,
-                            "Tradable",
-                        );
-                        ui.selectable_value(&mut self.selected_asset_type, AssetType::Cash, "Cash");
-                    });
+                ui.heading(tr("Add Assets"));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label(tr("Select asset type"))
+                        .selected_text(tr(&format!("{:?}", self.selected_asset_type)))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::RealEstate,
+                                tr("Real Estate"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::Loan,
+                                tr("Loan"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::Tradable,
+                                tr("Tradable"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::Cash,
+                                tr("Cash"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::Depreciating,
+                                tr("Depreciating"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::Bond,
+                                tr("Bond"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::CreditCard,
+                                tr("Credit Card"),
+                            );
+                            ui.selectable_value(
+                                &mut self.selected_asset_type,
+                                AssetType::EquityGrant,
+                                tr("Equity Grant"),
+                            );
+                        });
+                    if !self.application_settings.templates.is_empty() {
+                        self.templates_ui(ui);
+                    }
+                });
                 match self.selected_asset_type {
                     AssetType::RealEstate => {
-                        if ui.button("Add Real Estate").clicked() {
+                        if ui.button(tr("Add Real Estate")).clicked() {
                             self.portfolio
                                 .add_asset(Asset::RealEstate(RealEstate::default()));
                         }
                     }
                     AssetType::Loan => {
-                        if ui.button("Add Loan").clicked() {
+                        if ui.button(tr("Add Loan")).clicked() {
                             self.portfolio.add_asset(Asset::Loan(Loan::default()));
                         }
                     }
                     AssetType::Tradable => {
-                        if ui.button("Add Tradable").clicked() {
+                        if ui.button(tr("Add Tradable")).clicked() {
                             self.portfolio
                                 .add_asset(Asset::Tradable(Tradable::default()));
                         }
                     }
                     AssetType::Cash => {
-                        if ui.button("Add Cash").clicked() {
+                        if ui.button(tr("Add Cash")).clicked() {
                             self.portfolio.add_asset(Asset::Cash(Cash::default()));
                         }
                     }
+                    AssetType::Depreciating => {
+                        if ui.button(tr("Add Depreciating")).clicked() {
+                            self.portfolio
+                                .add_asset(Asset::Depreciating(Depreciating::default()));
+                        }
+                    }
+                    AssetType::Bond => {
+                        if ui.button(tr("Add Bond")).clicked() {
+                            self.portfolio.add_asset(Asset::Bond(Bond::default()));
+                        }
+                    }
+                    AssetType::CreditCard => {
+                        if ui.button(tr("Add Credit Card")).clicked() {
+                            self.portfolio
+                                .add_asset(Asset::CreditCard(CreditCard::default()));
+                        }
+                    }
+                    AssetType::EquityGrant => {
+                        if ui.button(tr("Add Equity Grant")).clicked() {
+                            self.portfolio
+                                .add_asset(Asset::EquityGrant(EquityGrant::default()));
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button(tr("Import CSV")).clicked() {
+                    self.csv_import_dialog.open = true;
+                }
+                if ui.button(tr("Import Bank Statement")).clicked() {
+                    self.statement_import_dialog.open = true;
                 }
             });
             ui.separator();
-            ui.collapsing("Application settings", |ui| {
+            ui.collapsing(tr("Application settings"), |ui| {
                 egui::global_theme_preference_buttons(ui);
                 ui.horizontal(|ui| {
-                    ui.label("Stroke width:");
+                    ui.label(tr("Stroke width:"));
                     ui.add(egui::Slider::new(
                         &mut self.application_settings.stroke_width,
                         0.0..=10.0,
                     ));
                 });
                 ui.horizontal(|ui| {
-                    ui.label("Resolution:");
+                    ui.label(tr("Resolution:"));
                     ui.add(egui::Slider::new(
                         &mut self.application_settings.interval_days,
                         1..=365,
                     ));
                 });
                 ui.horizontal(|ui| {
-                    ui.label("End date:");
+                    ui.label(tr("End date:"));
                     ui.horizontal(|ui| {
-                        ui.label("Year");
+                        ui.label(tr("Year"));
                         ui.add(egui::Slider::new(
                             &mut self.application_settings.end_date.0,
                             Utc::now().year()..=Utc::now().year() + 50,
@@ -190,14 +507,14 @@ impl eframe::App for WealthTrackerApp {
                     };
 
                     ui.horizontal(|ui| {
-                        ui.label("Month");
+                        ui.label(tr("Month"));
                         ui.add(egui::Slider::new(
                             &mut self.application_settings.end_date.1,
                             start_month..=12,
                         ));
                     });
                 });
-                egui::ComboBox::from_label("Currency")
+                egui::ComboBox::from_label(tr("Currency"))
                     .selected_text(self.application_settings.currency.clone())
                     .show_ui(ui, |ui| {
                         for currency in CURRENCY_SYMBOLS {
@@ -208,97 +525,624 @@ impl eframe::App for WealthTrackerApp {
                             );
                         }
                     });
+                egui::ComboBox::from_label(tr("Language"))
+                    .selected_text(self.application_settings.locale.label())
+                    .show_ui(ui, |ui| {
+                        for locale in Locale::ALL {
+                            ui.selectable_value(
+                                &mut self.application_settings.locale,
+                                locale,
+                                locale.label(),
+                            );
+                        }
+                    });
+            });
+            ui.collapsing(tr("Fees"), |ui| {
+                let end_date = self.application_settings.end_date();
+                let currency = &self.application_settings.currency;
+                ui.label(format!("{} {}:", tr("Fees paid until"), end_date));
+                egui::Grid::new("fee_report").striped(true).show(ui, |ui| {
+                    for asset in &self.portfolio.assets {
+                        let fees = asset.fees_paid(end_date);
+                        if fees > 0.0 {
+                            ui.label(asset.name());
+                            ui.label(format_amount(fees, currency));
+                            ui.end_row();
+                        }
+                    }
+                    ui.strong(tr("Total"));
+                    ui.strong(format_amount(self.portfolio.total_fees(end_date), currency));
+                    ui.end_row();
+                });
+            });
+            ui.collapsing(tr("Goals"), |ui| {
+                let today = Utc::now().date_naive();
+                let currency = self.application_settings.currency.clone();
+                let statuses: Vec<GoalStatus> = self
+                    .portfolio
+                    .goals
+                    .iter()
+                    .map(|goal| self.portfolio.goal_status(goal, today))
+                    .collect();
+                let mut index_to_remove = None;
+                for (index, (goal, status)) in
+                    self.portfolio.goals.iter_mut().zip(statuses).enumerate()
+                {
+                    ui.group(|ui| {
+                        goal.ui_edit(ui, &currency);
+                        ui.horizontal(|ui| {
+                            let color = match status {
+                                GoalStatus::Reached | GoalStatus::OnTrack => egui::Color32::GREEN,
+                                GoalStatus::Behind => egui::Color32::RED,
+                            };
+                            ui.colored_label(color, tr(status.label()));
+                            if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
+                                index_to_remove = Some(index);
+                            }
+                        });
+                    });
+                }
+                if let Some(index) = index_to_remove {
+                    let goal = self.portfolio.goals.remove(index);
+                    self.deleted = Some(Deleted {
+                        name: goal.name,
+                        at: ui.input(|input| input.time),
+                        recorded: false,
+                    });
+                }
+                if ui.button(tr("Add Goal")).clicked() {
+                    self.portfolio.goals.push(Goal::default());
+                }
+            });
+            ui.collapsing(tr("Annual Report"), |ui| {
+                let report_date = self
+                    .report_date
+                    .get_or_insert_with(|| Utc::now().date_naive());
+                ui.horizontal(|ui| {
+                    ui.label(tr("Report Date: "));
+                    date_edit(ui, report_date);
+                });
+                if ui.button(tr("Generate Report")).clicked() {
+                    let html = annual_report(
+                        &self.portfolio,
+                        &ReportOptions {
+                            title: &self.label,
+                            currency: &self.application_settings.currency,
+                            date: *report_date,
+                            end_date: self.application_settings.end_date(),
+                            interval_days: self.application_settings.interval_days,
+                        },
+                    );
+                    let file_name = format!("wealth_report_{}.html", report_date);
+                    self.export_status =
+                        Some(match save_file(&file_name, html.as_bytes(), "text/html") {
+                            Ok(status) | Err(status) => status,
+                        });
+                }
+                ui.label(tr(
+                    "Open the report in a browser and print it to save it as PDF.",
+                ));
             });
             ui.separator();
-            ui.heading("Assets");
-            egui::ScrollArea::new(true).show(ui, |ui| {
-                let mut id_to_delete: Uuid = Uuid::nil();
-                for asset in &mut self.portfolio.assets {
-                    let header_name = asset.name();
-                    let colored_header = egui::RichText::new(header_name).color(asset.color());
-                    ui.horizontal(|ui| {
-                        egui::CollapsingHeader::new(colored_header)
-                            .id_salt(asset.uuid())
+            ui.horizontal(|ui| {
+                ui.heading(tr("Assets"));
+                let asset_view = &mut self.application_settings.asset_view;
+                ui.selectable_value(
+                    asset_view,
+                    AssetView::List,
+                    egui_material_icons::icons::ICON_LIST,
+                )
+                .on_hover_text(tr("List"));
+                ui.selectable_value(
+                    asset_view,
+                    AssetView::Table,
+                    egui_material_icons::icons::ICON_TABLE,
+                )
+                .on_hover_text(tr("Table"));
+
+                let tags = self.portfolio.tags();
+                if !tags.is_empty() || self.tag_filter.is_some() {
+                    ui.label(tr("Tag: "));
+                    egui::ComboBox::from_id_salt("tag_filter")
+                        .selected_text(self.tag_filter.as_deref().unwrap_or(tr("All")))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.tag_filter, None, tr("All"));
+                            for tag in tags {
+                                ui.selectable_value(&mut self.tag_filter, Some(tag.clone()), tag);
+                            }
+                        });
+                }
+            });
+            if self.application_settings.asset_view == AssetView::Table {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    self.application_settings.asset_table.show(
+                        ui,
+                        &mut self.portfolio,
+                        Utc::now().date_naive(),
+                        self.application_settings.end_date(),
+                        &self.application_settings.currency,
+                        self.tag_filter.as_deref(),
+                    );
+                });
+            } else {
+                egui::ScrollArea::new(true).show(ui, |ui| {
+                    let mut action: Option<AssetAction> = None;
+                    let cash_assets: Vec<(Uuid, String)> = self
+                        .portfolio
+                        .assets
+                        .iter()
+                        .filter(|asset| matches!(asset, Asset::Cash(_)))
+                        .map(|asset| (asset.uuid(), asset.name()))
+                        .collect();
+                    let groups = self.portfolio.groups();
+                    let currency = &self.application_settings.currency;
+                    let tag_filter = &self.tag_filter;
+                    let shown = |asset: &Asset, group: &str| {
+                        asset.meta().group == group
+                            && tag_filter
+                                .as_ref()
+                                .map_or(true, |tag| asset.meta().tags.contains(tag))
+                    };
+
+                    // A collapsible section per group, then the assets without a group.
+                    for group in &groups {
+                        let count = self
+                            .portfolio
+                            .assets
+                            .iter()
+                            .filter(|asset| shown(asset, group))
+                            .count();
+                        if count == 0 {
+                            continue;
+                        }
+                        egui::CollapsingHeader::new(format!("{} ({})", group, count))
+                            .id_salt(("asset_group", group))
+                            .default_open(true)
                             .show(ui, |ui| {
-                                asset.ui_edit(ui, self.application_settings.currency.clone() + " ");
-                                if asset.should_delete() {
-                                    id_to_delete = asset.uuid();
+                                for asset in self
+                                    .portfolio
+                                    .assets
+                                    .iter_mut()
+                                    .filter(|asset| shown(asset, group))
+                                {
+                                    asset_ui(
+                                        ui,
+                                        asset,
+                                        currency,
+                                        &cash_assets,
+                                        &groups,
+                                        &mut action,
+                                    );
                                 }
                             });
-                        if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
-                            id_to_delete = asset.uuid();
+                    }
+                    for asset in self
+                        .portfolio
+                        .assets
+                        .iter_mut()
+                        .filter(|asset| shown(asset, ""))
+                    {
+                        asset_ui(ui, asset, currency, &cash_assets, &groups, &mut action);
+                    }
+
+                    match action {
+                        Some(AssetAction::Delete(uuid)) => {
+                            if let Some(asset) = self
+                                .portfolio
+                                .assets
+                                .iter()
+                                .find(|asset| asset.uuid() == uuid)
+                            {
+                                self.deleted = Some(Deleted {
+                                    name: asset.name(),
+                                    at: ui.input(|input| input.time),
+                                    recorded: false,
+                                });
+                            }
+                            self.portfolio.delete_asset(uuid);
                         }
-                    });
-                }
-                self.portfolio.delete_asset(id_to_delete);
-            });
+                        Some(AssetAction::Duplicate(uuid)) => self.portfolio.duplicate_asset(uuid),
+                        Some(AssetAction::SaveTemplate(uuid)) => {
+                            if let Some(asset) = self
+                                .portfolio
+                                .assets
+                                .iter()
+                                .find(|asset| asset.uuid() == uuid)
+                            {
+                                self.application_settings
+                                    .templates
+                                    .push(asset.to_template());
+                            }
+                        }
+                        Some(AssetAction::Move { dragged, target }) => {
+                            self.portfolio.move_asset(dragged, target)
+                        }
+                        None => {}
+                    }
+                });
+            }
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading(&self.label);
-            // Plot the portfolio value over time.
-            let start_date = Utc::now().date_naive();
-            let end_date = NaiveDate::from_ymd_opt(
-                self.application_settings.end_date.0,
-                self.application_settings.end_date.1,
-                1,
-            )
-            .unwrap();
-            let mut lines = Vec::new();
-            for asset in &self.portfolio.assets {
-                let line = create_plot_line(
-                    asset.clone(),
-                    start_date,
-                    end_date,
-                    self.application_settings.interval_days,
-                )
-                .name(asset.name())
-                .width(self.application_settings.stroke_width)
-                .color(asset.color());
-                lines.push(line);
+            // Plot the portfolio value over time, including any recorded history.
+            let today = Utc::now().date_naive();
+            let start_date = self
+                .portfolio
+                .history_start()
+                .map_or(today, |history_start| history_start.min(today));
+            let end_date = self.application_settings.end_date();
+
+            // A requested screenshot of the plot has arrived: crop it and save it as PNG.
+            let screenshot = ctx.input(|input| {
+                input.events.iter().find_map(|event| match event {
+                    egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                    _ => None,
+                })
+            });
+            if let (Some(image), Some(plot_rect)) = (screenshot, self.plot_rect) {
+                if std::mem::take(&mut self.png_export_pending) {
+                    let image = image.region(&plot_rect, Some(ctx.pixels_per_point()));
+                    let file_name = format!("wealth_projection_{}.png", today);
+                    self.export_status = Some(
+                        match encode_png(&image)
+                            .and_then(|png| save_file(&file_name, &png, "image/png"))
+                        {
+                            Ok(status) | Err(status) => status,
+                        },
+                    );
+                }
             }
-            let portfolio_line = create_portfolio_plot_line(
-                &self.portfolio,
-                start_date,
-                end_date,
-                self.application_settings.interval_days,
-            );
-            lines.push(portfolio_line);
-            let (max, min) = if self.portfolio.assets.is_empty() {
-                (0.0, 0.0)
-            } else {
-                (
-                    self.portfolio.max_value(
+
+            // Export the plotted series, or the chart itself.
+            ui.horizontal(|ui| {
+                let series = || {
+                    let series = if self.plot_by_group() {
+                        group_series
+                    } else {
+                        plot_series
+                    };
+                    series(
+                        &self.portfolio,
                         start_date,
                         end_date,
                         self.application_settings.interval_days,
-                    ),
-                    self.portfolio.min_value(
+                    )
+                };
+                let mut saved = None;
+                for format in [ExportFormat::Csv, ExportFormat::Json] {
+                    let label = format!("{} {}", tr("Export"), format.extension().to_uppercase());
+                    if ui.button(label).clicked() {
+                        let file_name =
+                            format!("wealth_projection_{}.{}", today, format.extension());
+                        saved = Some(save_file(
+                            &file_name,
+                            format.export(&series()).as_bytes(),
+                            format.mime_type(),
+                        ));
+                    }
+                }
+                if ui.button(format!("{} SVG", tr("Export"))).clicked() {
+                    let svg =
+                        series_to_svg(&series(), &self.label, &self.application_settings.currency);
+                    let file_name = format!("wealth_projection_{}.svg", today);
+                    saved = Some(save_file(&file_name, svg.as_bytes(), "image/svg+xml"));
+                }
+                if ui.button(format!("{} PNG", tr("Export"))).clicked() {
+                    self.png_export_pending = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+                }
+                if let Some(Ok(status) | Err(status)) = saved {
+                    self.export_status = Some(status);
+                }
+                if let Some(status) = &self.export_status {
+                    ui.label(status);
+                }
+            });
+
+            // Chart mode, and the date of the allocation views.
+            ui.horizontal(|ui| {
+                let chart_mode = &mut self.application_settings.chart_mode;
+                ui.selectable_value(chart_mode, ChartMode::Lines, tr("Lines"));
+                ui.selectable_value(chart_mode, ChartMode::StackedArea, tr("Stacked"));
+                ui.selectable_value(chart_mode, ChartMode::AllocationPie, tr("Pie"));
+                ui.selectable_value(chart_mode, ChartMode::AllocationBar, tr("Bars"));
+                if matches!(
+                    chart_mode,
+                    ChartMode::AllocationPie | ChartMode::AllocationBar
+                ) {
+                    ui.separator();
+                    ui.label(tr("Date: "));
+                    date_edit(ui, self.allocation_date.get_or_insert(today));
+                }
+                if *chart_mode == ChartMode::Lines && !self.portfolio.groups().is_empty() {
+                    ui.separator();
+                    ui.checkbox(&mut self.application_settings.plot_by_group, tr("By group"));
+                }
+            });
+            let allocation_date = self.allocation_date.unwrap_or(today);
+            let currency = self.application_settings.currency.clone();
+            let plot_by_group = self.plot_by_group();
+            // The values are computed once per frame, for both the lines and the hover text.
+            let hover_series = match self.application_settings.chart_mode {
+                ChartMode::Lines if plot_by_group => group_series(
+                    &self.portfolio,
+                    start_date,
+                    end_date,
+                    self.application_settings.interval_days,
+                ),
+                ChartMode::Lines | ChartMode::StackedArea => plot_series(
+                    &self.portfolio,
+                    start_date,
+                    end_date,
+                    self.application_settings.interval_days,
+                ),
+                ChartMode::AllocationPie | ChartMode::AllocationBar => Vec::new(),
+            };
+
+            let plot_rect = match self.application_settings.chart_mode {
+                ChartMode::Lines if plot_by_group => {
+                    let lines: Vec<_> = hover_series
+                        .iter()
+                        .map(|series| {
+                            create_series_plot_line(series)
+                                .width(self.application_settings.stroke_width)
+                        })
+                        .collect();
+                    wealth_plot(&currency, &hover_series)
+                        .show(ui, |plot_ui| {
+                            for line in lines {
+                                plot_ui.line(line);
+                            }
+                        })
+                        .response
+                        .rect
+                }
+                ChartMode::Lines => {
+                    // One series per asset, in portfolio order, then the portfolio total.
+                    let (asset_series, total_series) =
+                        hover_series.split_at(self.portfolio.assets.len());
+                    let mut lines = Vec::new();
+                    for (asset, series) in self.portfolio.assets.iter().zip(asset_series) {
+                        lines.push(
+                            create_series_plot_line(series)
+                                .width(self.application_settings.stroke_width),
+                        );
+                        if let Some(unvested_line) = create_unvested_plot_line(
+                            asset,
+                            start_date,
+                            end_date,
+                            self.application_settings.interval_days,
+                        ) {
+                            lines.push(unvested_line.width(self.application_settings.stroke_width));
+                        }
+                    }
+                    lines.extend(
+                        total_series
+                            .iter()
+                            .map(|series| create_series_plot_line(series).width(2.0)),
+                    );
+
+                    // Keep the sum of the asset values in view.
+                    let sums: Vec<f32> = (0..total_series[0].points.len())
+                        .map(|index| {
+                            asset_series
+                                .iter()
+                                .map(|series| series.points[index].1)
+                                .sum()
+                        })
+                        .collect();
+                    let (max, min) = if asset_series.is_empty() {
+                        (0.0, 0.0)
+                    } else {
+                        (
+                            sums.iter().copied().fold(0.0, f32::max),
+                            sums.iter().copied().fold(f32::MAX, f32::min),
+                        )
+                    };
+
+                    wealth_plot(&currency, &hover_series)
+                        .include_y(max)
+                        .include_y(min)
+                        .show(ui, |plot_ui| {
+                            for line in lines {
+                                plot_ui.line(line);
+                            }
+                        })
+                        .response
+                        .rect
+                }
+                ChartMode::StackedArea => {
+                    let bands = create_stacked_area_bands(
+                        &self.portfolio,
                         start_date,
                         end_date,
                         self.application_settings.interval_days,
-                    ),
-                )
+                    );
+                    let net_worth_line =
+                        hover_series
+                            .last()
+                            .map(create_series_plot_line)
+                            .map(|line| {
+                                line.name(tr("Net Worth"))
+                                    .width(self.application_settings.stroke_width)
+                            });
+                    wealth_plot(&currency, &hover_series)
+                        .show(ui, |plot_ui| {
+                            for band in bands {
+                                plot_ui.add(band);
+                            }
+                            if let Some(net_worth_line) = net_worth_line {
+                                plot_ui.line(net_worth_line);
+                            }
+                        })
+                        .response
+                        .rect
+                }
+                ChartMode::AllocationPie => {
+                    allocation_pie(ui, &self.portfolio, allocation_date, &currency).rect
+                }
+                ChartMode::AllocationBar => {
+                    let bar_charts = allocation_bar_charts(&self.portfolio, allocation_date);
+                    Plot::new("allocation_bars")
+                        .legend(Legend::default())
+                        .y_axis_label(format!("{} ({})", tr("Value"), currency))
+                        .y_axis_formatter(|mark, _range| format_compact(mark.value))
+                        .show_x(false)
+                        .x_axis_formatter(|_, _| String::new())
+                        .include_y(0.0)
+                        .show(ui, |plot_ui| {
+                            for bar_chart in bar_charts {
+                                plot_ui.bar_chart(bar_chart);
+                            }
+                        })
+                        .response
+                        .rect
+                }
             };
+            self.plot_rect = Some(plot_rect);
+        });
 
-            Plot::new("wealth_over_time")
-                .legend(Legend::default())
-                .include_y(0.0)
-                .include_y(max)
-                .include_y(min)
-                .clamp_grid(true)
-                .x_axis_formatter(|x, _range| {
-                    let timestamp = x.value as i64;
-                    match Utc.timestamp_opt(timestamp, 0) {
-                        chrono::LocalResult::Single(date) => date.format("%Y-%m-%d").to_string(),
-                        _ => "Invalid date".to_owned(),
-                    }
-                })
-                .show(ui, |plot_ui| {
-                    for line in lines {
-                        plot_ui.line(line);
-                    }
-                });
+        // A dropped file goes to the open import dialog, or the one in front if both are open.
+        if let Some(text) = dropped_file_text(ctx) {
+            let top_layer = ctx.memory(|memory| memory.areas().top_layer_id(egui::Order::Middle));
+            let statement_in_front = top_layer == Some(StatementImportDialog::layer_id());
+            match (
+                self.csv_import_dialog.open,
+                self.statement_import_dialog.open,
+            ) {
+                (true, true) if statement_in_front => self.statement_import_dialog.set_text(text),
+                (true, _) => self.csv_import_dialog.set_text(text),
+                (false, true) => self.statement_import_dialog.set_text(text),
+                (false, false) => {}
+            }
+        }
+        self.csv_import_dialog.show(
+            ctx,
+            &mut self.portfolio,
+            &self.application_settings.currency,
+        );
+        self.statement_import_dialog.show(ctx, &mut self.portfolio);
+        self.undo_toast(ctx);
+
+        // Record the edits of this frame as an undo step, once a drag or text edit is finished
+        // so that it becomes a single step. The portfolio only changes on input, so frames
+        // without any are not compared.
+        self.recorded &= ctx.input(|input| {
+            input.events.iter().all(|event| {
+                matches!(
+                    event,
+                    egui::Event::PointerMoved(_)
+                        | egui::Event::MouseMoved(_)
+                        | egui::Event::PointerGone
+                )
+            })
         });
+        if !self.recorded
+            && !ctx.input(|input| input.pointer.any_down())
+            && !ctx.wants_keyboard_input()
+        {
+            self.record_history();
+        }
     }
 }
+
+/// One asset in the side panel: a drag handle for reordering, a collapsible editor, and
+/// buttons to duplicate, save as template and delete it.
+fn asset_ui(
+    ui: &mut egui::Ui,
+    asset: &mut Asset,
+    currency: &str,
+    cash_assets: &[(Uuid, String)],
+    groups: &[String],
+    action: &mut Option<AssetAction>,
+) {
+    let colored_header = egui::RichText::new(asset.name()).color(asset.color());
+    let row = ui.horizontal(|ui| {
+        ui.dnd_drag_source(
+            egui::Id::new(("asset_drag", asset.uuid())),
+            asset.uuid(),
+            |ui| {
+                ui.label(egui_material_icons::icons::ICON_DRAG_INDICATOR);
+            },
+        )
+        .response
+        .on_hover_text(tr("Drag to reorder"));
+        egui::CollapsingHeader::new(colored_header)
+            .id_salt(asset.uuid())
+            .show(ui, |ui| {
+                asset.ui_edit(ui, currency.to_owned());
+                let uuid = asset.uuid();
+                group_edit(
+                    ui,
+                    ("asset_group", uuid),
+                    &mut asset.meta_mut().group,
+                    groups,
+                );
+                tags_edit(ui, ("asset_tags", uuid), &mut asset.meta_mut().tags);
+                if let Some(target) = asset.payout_target_mut() {
+                    payout_target_edit(ui, ("payout_target", uuid), target, cash_assets);
+                }
+                let ledger_kinds = asset.ledger_kinds();
+                if let Some(ledger) = asset.ledger_mut() {
+                    ui.collapsing(
+                        format!("{} ({})", tr("Ledger"), ledger.entries().len()),
+                        |ui| {
+                            ledger.ui_edit(ui, uuid, currency, ledger_kinds);
+                        },
+                    );
+                }
+                if asset.should_delete() {
+                    *action = Some(AssetAction::Delete(asset.uuid()));
+                }
+            });
+        if ui
+            .button(egui_material_icons::icons::ICON_CONTENT_COPY)
+            .on_hover_text(tr("Duplicate"))
+            .clicked()
+        {
+            *action = Some(AssetAction::Duplicate(asset.uuid()));
+        }
+        if ui
+            .button(egui_material_icons::icons::ICON_BOOKMARK_ADD)
+            .on_hover_text(tr("Save as template"))
+            .clicked()
+        {
+            *action = Some(AssetAction::SaveTemplate(asset.uuid()));
+        }
+        if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
+            *action = Some(AssetAction::Delete(asset.uuid()));
+        }
+    });
+
+    // Another asset dragged onto this one takes its place.
+    if row.response.dnd_hover_payload::<Uuid>().is_some() {
+        ui.painter().rect_stroke(
+            row.response.rect,
+            2.0,
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+    }
+    if let Some(dragged) = row.response.dnd_release_payload::<Uuid>() {
+        if *dragged != asset.uuid() {
+            *action = Some(AssetAction::Move {
+                dragged: *dragged,
+                target: asset.uuid(),
+            });
+        }
+    }
+}
+
+/// The wealth-over-time plot with dates on the x axis and compact amounts on the y axis. Hovering
+/// shows the values of the given series at the closest date.
+fn wealth_plot<'a>(currency: &'a str, series: &'a [PlotSeries]) -> Plot<'a> {
+    Plot::new("wealth_over_time")
+        .legend(Legend::default())
+        .y_axis_label(format!("{} ({})", tr("Value"), currency))
+        .y_axis_formatter(|mark, _range| format_compact(mark.value))
+        .include_y(0.0)
+        .clamp_grid(true)
+        .x_axis_formatter(|x, _range| format_timestamp(x.value))
+        .label_formatter(move |_name, point| hover_text(series, currency, point.x))
+}
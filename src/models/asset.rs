@@ -1,8 +1,20 @@
-use super::{Cash, Loan, RealEstate, Tradable};
+use super::assets::{depreciating::DepreciationModel, tradable::ContributionFrequency};
+use super::ledger::{Ledger, LedgerEntryKind};
+use super::{
+    Bond, Cash, CreditCard, Depreciating, EquityGrant, Loan, RateSchedule, RealEstate, Tradable,
+};
 use chrono::NaiveDate;
 use egui::Ui;
+use std::ops::RangeInclusive;
 use uuid::Uuid;
 
+/// How the user has organized an asset, shared by all asset types.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AssetMeta {
+    pub group: String, // side panel section and plot line the asset belongs to, if not empty
+    pub tags: Vec<String>,
+}
+
 pub trait AssetTrait {
     fn value(&self, date: NaiveDate) -> f32;
     fn name(&self) -> String;
@@ -13,6 +25,38 @@ pub trait AssetTrait {
     }
     fn color(&self) -> egui::Color32;
     fn is_growth(&self) -> bool;
+    /// Value the owner does not have yet, e.g. unvested shares. Not part of the portfolio total.
+    fn unvested_value(&self, _date: NaiveDate) -> f32 {
+        0.0
+    }
+    /// Date of the earliest recorded (rather than projected) value, if the asset has any history.
+    fn history_start(&self) -> Option<NaiveDate> {
+        None
+    }
+    /// Total fees charged by the asset from acquisition up to `date`.
+    fn fees_paid(&self, _date: NaiveDate) -> f32 {
+        0.0
+    }
+    /// Cash paid out by the asset up to and including `date`, as (payment date, amount) pairs.
+    fn payouts(&self, _date: NaiveDate) -> Vec<(NaiveDate, f32)> {
+        Vec::new()
+    }
+    /// The cash asset receiving the payouts. Without a target the asset keeps them in its value.
+    fn payout_target(&self) -> Option<Uuid> {
+        None
+    }
+    /// Editable payout target, or `None` if the asset never pays anything out.
+    fn payout_target_mut(&mut self) -> Option<&mut Option<Uuid>> {
+        None
+    }
+    /// The asset's ledger of recorded transactions, if the asset keeps one.
+    fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        None
+    }
+    /// Kinds of ledger entries that make sense for the asset.
+    fn ledger_kinds(&self) -> &'static [LedgerEntryKind] {
+        &LedgerEntryKind::ALL
+    }
 }
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub enum Asset {
@@ -20,6 +64,10 @@ pub enum Asset {
     Loan(Loan),
     Tradable(Tradable),
     Cash(Cash),
+    Depreciating(Depreciating),
+    Bond(Bond),
+    CreditCard(CreditCard),
+    EquityGrant(EquityGrant),
 }
 
 impl AssetTrait for Asset {
@@ -29,6 +77,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.value(date),
             Asset::Tradable(tradable) => tradable.value(date),
             Asset::Cash(cash) => cash.value(date),
+            Asset::Depreciating(depreciating) => depreciating.value(date),
+            Asset::Bond(bond) => bond.value(date),
+            Asset::CreditCard(credit_card) => credit_card.value(date),
+            Asset::EquityGrant(equity_grant) => equity_grant.value(date),
         }
     }
 
@@ -38,6 +90,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.name.clone(),
             Asset::Tradable(tradable) => tradable.name.clone(),
             Asset::Cash(cash) => cash.name.clone(),
+            Asset::Depreciating(depreciating) => depreciating.name.clone(),
+            Asset::Bond(bond) => bond.name.clone(),
+            Asset::CreditCard(credit_card) => credit_card.name.clone(),
+            Asset::EquityGrant(equity_grant) => equity_grant.name.clone(),
         }
     }
     fn ui_edit(&mut self, ui: &mut Ui, currency: String) -> bool {
@@ -46,6 +102,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.ui_edit(ui, currency),
             Asset::Tradable(tradable) => tradable.ui_edit(ui, currency),
             Asset::Cash(cash) => cash.ui_edit(ui, currency),
+            Asset::Depreciating(depreciating) => depreciating.ui_edit(ui, currency),
+            Asset::Bond(bond) => bond.ui_edit(ui, currency),
+            Asset::CreditCard(credit_card) => credit_card.ui_edit(ui, currency),
+            Asset::EquityGrant(equity_grant) => equity_grant.ui_edit(ui, currency),
         }
     }
     fn uuid(&self) -> Uuid {
@@ -54,6 +114,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.uuid,
             Asset::Tradable(tradable) => tradable.uuid,
             Asset::Cash(cash) => cash.uuid,
+            Asset::Depreciating(depreciating) => depreciating.uuid,
+            Asset::Bond(bond) => bond.uuid,
+            Asset::CreditCard(credit_card) => credit_card.uuid,
+            Asset::EquityGrant(equity_grant) => equity_grant.uuid,
         }
     }
     fn should_delete(&self) -> bool {
@@ -62,6 +126,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.should_delete(),
             Asset::Tradable(tradable) => tradable.should_delete(),
             Asset::Cash(cash) => cash.should_delete(),
+            Asset::Depreciating(depreciating) => depreciating.should_delete(),
+            Asset::Bond(bond) => bond.should_delete(),
+            Asset::CreditCard(credit_card) => credit_card.should_delete(),
+            Asset::EquityGrant(equity_grant) => equity_grant.should_delete(),
         }
     }
     fn color(&self) -> egui::Color32 {
@@ -70,6 +138,10 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.color(),
             Asset::Tradable(tradable) => tradable.color(),
             Asset::Cash(cash) => cash.color(),
+            Asset::Depreciating(depreciating) => depreciating.color(),
+            Asset::Bond(bond) => bond.color(),
+            Asset::CreditCard(credit_card) => credit_card.color(),
+            Asset::EquityGrant(equity_grant) => equity_grant.color(),
         }
     }
     fn is_growth(&self) -> bool {
@@ -78,7 +150,386 @@ impl AssetTrait for Asset {
             Asset::Loan(loan) => loan.is_growth(),
             Asset::Tradable(tradable) => tradable.is_growth(),
             Asset::Cash(cash) => cash.is_growth(),
+            Asset::Depreciating(depreciating) => depreciating.is_growth(),
+            Asset::Bond(bond) => bond.is_growth(),
+            Asset::CreditCard(credit_card) => credit_card.is_growth(),
+            Asset::EquityGrant(equity_grant) => equity_grant.is_growth(),
+        }
+    }
+    fn payouts(&self, date: NaiveDate) -> Vec<(NaiveDate, f32)> {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.payouts(date),
+            Asset::Loan(loan) => loan.payouts(date),
+            Asset::Tradable(tradable) => tradable.payouts(date),
+            Asset::Cash(cash) => cash.payouts(date),
+            Asset::Depreciating(depreciating) => depreciating.payouts(date),
+            Asset::Bond(bond) => bond.payouts(date),
+            Asset::CreditCard(credit_card) => credit_card.payouts(date),
+            Asset::EquityGrant(equity_grant) => equity_grant.payouts(date),
+        }
+    }
+    fn payout_target(&self) -> Option<Uuid> {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.payout_target(),
+            Asset::Loan(loan) => loan.payout_target(),
+            Asset::Tradable(tradable) => tradable.payout_target(),
+            Asset::Cash(cash) => cash.payout_target(),
+            Asset::Depreciating(depreciating) => depreciating.payout_target(),
+            Asset::Bond(bond) => bond.payout_target(),
+            Asset::CreditCard(credit_card) => credit_card.payout_target(),
+            Asset::EquityGrant(equity_grant) => equity_grant.payout_target(),
+        }
+    }
+    fn payout_target_mut(&mut self) -> Option<&mut Option<Uuid>> {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.payout_target_mut(),
+            Asset::Loan(loan) => loan.payout_target_mut(),
+            Asset::Tradable(tradable) => tradable.payout_target_mut(),
+            Asset::Cash(cash) => cash.payout_target_mut(),
+            Asset::Depreciating(depreciating) => depreciating.payout_target_mut(),
+            Asset::Bond(bond) => bond.payout_target_mut(),
+            Asset::CreditCard(credit_card) => credit_card.payout_target_mut(),
+            Asset::EquityGrant(equity_grant) => equity_grant.payout_target_mut(),
+        }
+    }
+    fn unvested_value(&self, date: NaiveDate) -> f32 {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.unvested_value(date),
+            Asset::Loan(loan) => loan.unvested_value(date),
+            Asset::Tradable(tradable) => tradable.unvested_value(date),
+            Asset::Cash(cash) => cash.unvested_value(date),
+            Asset::Depreciating(depreciating) => depreciating.unvested_value(date),
+            Asset::Bond(bond) => bond.unvested_value(date),
+            Asset::CreditCard(credit_card) => credit_card.unvested_value(date),
+            Asset::EquityGrant(equity_grant) => equity_grant.unvested_value(date),
+        }
+    }
+    fn fees_paid(&self, date: NaiveDate) -> f32 {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.fees_paid(date),
+            Asset::Loan(loan) => loan.fees_paid(date),
+            Asset::Tradable(tradable) => tradable.fees_paid(date),
+            Asset::Cash(cash) => cash.fees_paid(date),
+            Asset::Depreciating(depreciating) => depreciating.fees_paid(date),
+            Asset::Bond(bond) => bond.fees_paid(date),
+            Asset::CreditCard(credit_card) => credit_card.fees_paid(date),
+            Asset::EquityGrant(equity_grant) => equity_grant.fees_paid(date),
+        }
+    }
+    fn history_start(&self) -> Option<NaiveDate> {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.history_start(),
+            Asset::Loan(loan) => loan.history_start(),
+            Asset::Tradable(tradable) => tradable.history_start(),
+            Asset::Cash(cash) => cash.history_start(),
+            Asset::Depreciating(depreciating) => depreciating.history_start(),
+            Asset::Bond(bond) => bond.history_start(),
+            Asset::CreditCard(credit_card) => credit_card.history_start(),
+            Asset::EquityGrant(equity_grant) => equity_grant.history_start(),
+        }
+    }
+    fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.ledger_mut(),
+            Asset::Loan(loan) => loan.ledger_mut(),
+            Asset::Tradable(tradable) => tradable.ledger_mut(),
+            Asset::Cash(cash) => cash.ledger_mut(),
+            Asset::Depreciating(depreciating) => depreciating.ledger_mut(),
+            Asset::Bond(bond) => bond.ledger_mut(),
+            Asset::CreditCard(credit_card) => credit_card.ledger_mut(),
+            Asset::EquityGrant(equity_grant) => equity_grant.ledger_mut(),
+        }
+    }
+    fn ledger_kinds(&self) -> &'static [LedgerEntryKind] {
+        match self {
+            Asset::RealEstate(real_estate) => real_estate.ledger_kinds(),
+            Asset::Loan(loan) => loan.ledger_kinds(),
+            Asset::Tradable(tradable) => tradable.ledger_kinds(),
+            Asset::Cash(cash) => cash.ledger_kinds(),
+            Asset::Depreciating(depreciating) => depreciating.ledger_kinds(),
+            Asset::Bond(bond) => bond.ledger_kinds(),
+            Asset::CreditCard(credit_card) => credit_card.ledger_kinds(),
+            Asset::EquityGrant(equity_grant) => equity_grant.ledger_kinds(),
+        }
+    }
+}
+
+impl Asset {
+    pub fn asset_type(&self) -> AssetType {
+        match self {
+            Asset::RealEstate(_) => AssetType::RealEstate,
+            Asset::Loan(_) => AssetType::Loan,
+            Asset::Tradable(_) => AssetType::Tradable,
+            Asset::Cash(_) => AssetType::Cash,
+            Asset::Depreciating(_) => AssetType::Depreciating,
+            Asset::Bond(_) => AssetType::Bond,
+            Asset::CreditCard(_) => AssetType::CreditCard,
+            Asset::EquityGrant(_) => AssetType::EquityGrant,
+        }
+    }
+
+    pub fn name_mut(&mut self) -> &mut String {
+        match self {
+            Asset::RealEstate(real_estate) => &mut real_estate.name,
+            Asset::Loan(loan) => &mut loan.name,
+            Asset::Tradable(tradable) => &mut tradable.name,
+            Asset::Cash(cash) => &mut cash.name,
+            Asset::Depreciating(depreciating) => &mut depreciating.name,
+            Asset::Bond(bond) => &mut bond.name,
+            Asset::CreditCard(credit_card) => &mut credit_card.name,
+            Asset::EquityGrant(equity_grant) => &mut equity_grant.name,
+        }
+    }
+
+    /// The asset's group and tags.
+    pub fn meta(&self) -> &AssetMeta {
+        match self {
+            Asset::RealEstate(real_estate) => &real_estate.meta,
+            Asset::Loan(loan) => &loan.meta,
+            Asset::Tradable(tradable) => &tradable.meta,
+            Asset::Cash(cash) => &cash.meta,
+            Asset::Depreciating(depreciating) => &depreciating.meta,
+            Asset::Bond(bond) => &bond.meta,
+            Asset::CreditCard(credit_card) => &credit_card.meta,
+            Asset::EquityGrant(equity_grant) => &equity_grant.meta,
+        }
+    }
+
+    pub fn meta_mut(&mut self) -> &mut AssetMeta {
+        match self {
+            Asset::RealEstate(real_estate) => &mut real_estate.meta,
+            Asset::Loan(loan) => &mut loan.meta,
+            Asset::Tradable(tradable) => &mut tradable.meta,
+            Asset::Cash(cash) => &mut cash.meta,
+            Asset::Depreciating(depreciating) => &mut depreciating.meta,
+            Asset::Bond(bond) => &mut bond.meta,
+            Asset::CreditCard(credit_card) => &mut credit_card.meta,
+            Asset::EquityGrant(equity_grant) => &mut equity_grant.meta,
+        }
+    }
+
+    /// Rates the editors offer for [`Asset::rate_mut`].
+    pub fn rate_range(&self) -> RangeInclusive<f32> {
+        match self {
+            Asset::Loan(_) | Asset::CreditCard(_) => RateSchedule::LOAN_RATES,
+            Asset::Cash(_) | Asset::Bond(_) => RateSchedule::SAVINGS_RATES,
+            Asset::Depreciating(_) => RateSchedule::DEPRECIATION_RATES,
+            Asset::RealEstate(_) | Asset::Tradable(_) | Asset::EquityGrant(_) => {
+                RateSchedule::GROWTH_RATES
+            }
+        }
+    }
+
+    /// The annual rate (%) that drives the projection: growth, interest, coupon or
+    /// depreciation depending on the asset.
+    pub fn rate_mut(&mut self) -> &mut f32 {
+        match self {
+            Asset::RealEstate(real_estate) => &mut real_estate.rate_per_year,
+            Asset::Loan(loan) => &mut loan.rate_per_year,
+            Asset::Tradable(tradable) => &mut tradable.rate_per_year,
+            Asset::Cash(cash) => &mut cash.interest_rate,
+            Asset::Depreciating(depreciating) => &mut depreciating.depreciation_rate,
+            Asset::Bond(bond) => &mut bond.coupon_rate,
+            Asset::CreditCard(credit_card) => &mut credit_card.apr,
+            Asset::EquityGrant(equity_grant) => &mut equity_grant.price_growth_rate,
+        }
+    }
+
+    /// The annual rate of [`Asset::rate_mut`] in effect on `date`, which a rate schedule may
+    /// override.
+    pub fn rate_at(&self, date: NaiveDate) -> f32 {
+        match self {
+            Asset::RealEstate(real_estate) => RateSchedule::optional_rate_at(
+                &real_estate.rate_schedule,
+                real_estate.rate_per_year,
+                date,
+            ),
+            Asset::Loan(loan) => loan.rate_at(date),
+            Asset::Tradable(tradable) => RateSchedule::optional_rate_at(
+                &tradable.rate_schedule,
+                tradable.rate_per_year,
+                date,
+            ),
+            Asset::Cash(cash) => cash.interest_rate,
+            Asset::Depreciating(depreciating) => depreciating.depreciation_rate,
+            Asset::Bond(bond) => bond.coupon_rate,
+            Asset::CreditCard(credit_card) => credit_card.apr,
+            Asset::EquityGrant(equity_grant) => RateSchedule::optional_rate_at(
+                &equity_grant.rate_schedule,
+                equity_grant.price_growth_rate,
+                date,
+            ),
+        }
+    }
+
+    /// Whether the asset's model uses the rate of [`Asset::rate_mut`]. Straight line and fixed
+    /// residual depreciation do not.
+    pub fn rate_used(&self) -> bool {
+        match self {
+            Asset::Depreciating(depreciating) => {
+                depreciating.depreciation_model == DepreciationModel::DecliningBalance
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether a rate schedule sets the rate in effect on `date` instead of
+    /// [`Asset::rate_mut`].
+    pub fn rate_scheduled_at(&self, date: NaiveDate) -> bool {
+        let schedule = match self {
+            Asset::RealEstate(real_estate) => &real_estate.rate_schedule,
+            Asset::Loan(loan) => &loan.rate_schedule,
+            Asset::Tradable(tradable) => &tradable.rate_schedule,
+            Asset::EquityGrant(equity_grant) => &equity_grant.rate_schedule,
+            _ => return false,
+        };
+        schedule
+            .as_ref()
+            .is_some_and(|schedule| schedule.changes_rate_by(date))
+    }
+
+    /// Whether a contribution plan changes the contribution of [`Asset::contribution_mut`]
+    /// made on `date`.
+    pub fn contribution_planned_at(&self, date: NaiveDate) -> bool {
+        match self {
+            Asset::Tradable(tradable) => tradable
+                .contribution_plan
+                .changes_amount(tradable.acquisition_date, date),
+            Asset::Cash(cash) => cash
+                .contribution_plan
+                .changes_amount(cash.acquisition_date, date),
+            _ => false,
+        }
+    }
+
+    /// The contribution of [`Asset::contribution_mut`] made on `date`, which a contribution
+    /// plan may raise, pause or end, and how often it is made.
+    pub fn contribution_at(&self, date: NaiveDate) -> Option<(f32, ContributionFrequency)> {
+        match self {
+            Asset::Loan(loan) => Some((loan.principal_payment, loan.principal_frequency.clone())),
+            Asset::Tradable(tradable) => Some((
+                tradable.contribution_plan.amount(
+                    tradable.contribution,
+                    tradable.acquisition_date,
+                    date,
+                ),
+                tradable.contribution_frequency.clone(),
+            )),
+            Asset::Cash(cash) => Some((
+                cash.contribution_plan
+                    .amount(cash.contribution, cash.acquisition_date, date),
+                cash.contribution_frequency.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The regular contribution, or loan payment, and how often it is made. `None` for assets
+    /// without one.
+    pub fn contribution_mut(&mut self) -> Option<(&mut f32, ContributionFrequency)> {
+        match self {
+            Asset::Loan(loan) => Some((
+                &mut loan.principal_payment,
+                loan.principal_frequency.clone(),
+            )),
+            Asset::Tradable(tradable) => Some((
+                &mut tradable.contribution,
+                tradable.contribution_frequency.clone(),
+            )),
+            Asset::Cash(cash) => {
+                Some((&mut cash.contribution, cash.contribution_frequency.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// A copy of the asset with a new uuid, and the color that goes with it.
+    pub fn duplicate(&self) -> Asset {
+        let mut copy = self.clone();
+        let uuid = Uuid::new_v4();
+        match &mut copy {
+            Asset::RealEstate(real_estate) => {
+                (real_estate.uuid, real_estate.color) = (uuid, RealEstate::color_for(&uuid));
+            }
+            Asset::Loan(loan) => (loan.uuid, loan.color) = (uuid, Loan::color_for(&uuid)),
+            Asset::Tradable(tradable) => {
+                (tradable.uuid, tradable.color) = (uuid, Tradable::color_for(&uuid));
+            }
+            Asset::Cash(cash) => (cash.uuid, cash.color) = (uuid, Cash::color_for(&uuid)),
+            Asset::Depreciating(depreciating) => {
+                (depreciating.uuid, depreciating.color) = (uuid, Depreciating::color_for(&uuid));
+            }
+            Asset::Bond(bond) => (bond.uuid, bond.color) = (uuid, Bond::color_for(&uuid)),
+            Asset::CreditCard(credit_card) => {
+                (credit_card.uuid, credit_card.color) = (uuid, CreditCard::color_for(&uuid));
+            }
+            Asset::EquityGrant(equity_grant) => {
+                (equity_grant.uuid, equity_grant.color) = (uuid, EquityGrant::color_for(&uuid));
+            }
+        }
+        copy
+    }
+
+    /// A copy to start new assets from. What happened to this asset is left out: the ledger,
+    /// recorded balances and prices, the payout target and any sale or cashout. It is acquired
+    /// today, and its other dates, such as rate changes or a bond's maturity, keep the same
+    /// distance to the acquisition.
+    pub fn to_template(&self) -> Asset {
+        let mut template = self.duplicate();
+        if let Some(ledger) = template.ledger_mut() {
+            *ledger = Ledger::default();
+        }
+        if let Some(target) = template.payout_target_mut() {
+            *target = None;
+        }
+        let today = chrono::Utc::now().date_naive();
+        let shift_schedule = |schedule: &mut Option<RateSchedule>, offset| {
+            if let Some(schedule) = schedule {
+                schedule.shift_dates(offset);
+            }
+        };
+        match &mut template {
+            Asset::RealEstate(real_estate) => {
+                let offset = today - real_estate.acquisition_date;
+                real_estate.acquisition_date = today;
+                shift_schedule(&mut real_estate.rate_schedule, offset);
+                real_estate.sale_details = None;
+            }
+            Asset::Loan(loan) => {
+                let offset = today - loan.acquisition_date;
+                loan.acquisition_date = today;
+                shift_schedule(&mut loan.rate_schedule, offset);
+            }
+            Asset::Tradable(tradable) => {
+                let offset = today - tradable.acquisition_date;
+                tradable.acquisition_date = today;
+                shift_schedule(&mut tradable.rate_schedule, offset);
+                tradable.contribution_plan.shift_dates(offset);
+                tradable.holding = None;
+                tradable.cashout_details = None;
+            }
+            Asset::Cash(cash) => {
+                let offset = today - cash.acquisition_date;
+                cash.acquisition_date = today;
+                cash.contribution_plan.shift_dates(offset);
+                cash.balance_history.clear();
+            }
+            Asset::Depreciating(depreciating) => {
+                depreciating.acquisition_date = today;
+                depreciating.sale_date = None;
+            }
+            Asset::Bond(bond) => {
+                bond.maturity_date += today - bond.acquisition_date;
+                bond.acquisition_date = today;
+            }
+            Asset::CreditCard(credit_card) => credit_card.acquisition_date = today,
+            Asset::EquityGrant(equity_grant) => {
+                let offset = today - equity_grant.acquisition_date;
+                equity_grant.acquisition_date = today;
+                shift_schedule(&mut equity_grant.rate_schedule, offset);
+            }
         }
+        template
     }
 }
 
@@ -88,4 +539,8 @@ pub enum AssetType {
     Loan,
     Tradable,
     Cash,
+    Depreciating,
+    Bond,
+    CreditCard,
+    EquityGrant,
 }
@@ -0,0 +1,262 @@
+use chrono::Months;
+pub use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, date_edit, number_drag_value},
+    RateSchedule,
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum CouponFrequency {
+    Annual,
+    SemiAnnual,
+    Quarterly,
+    Monthly,
+}
+
+impl CouponFrequency {
+    pub fn payments_per_year(&self) -> u32 {
+        match self {
+            CouponFrequency::Annual => 1,
+            CouponFrequency::SemiAnnual => 2,
+            CouponFrequency::Quarterly => 4,
+            CouponFrequency::Monthly => 12,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Bond {
+    pub uuid: Uuid,
+    pub name: String,
+    pub face_value: f32,
+    pub purchase_price: f32,
+    pub coupon_rate: f32, // annual coupon (% of face value)
+    pub coupon_frequency: CouponFrequency,
+    pub acquisition_date: NaiveDate,
+    pub maturity_date: NaiveDate,
+    pub payout_target: Option<Uuid>, // cash asset receiving coupons and principal, if any
+    pub should_delete: bool,
+    pub color: egui::Color32,
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+impl Default for Bond {
+    fn default() -> Self {
+        let uuid = Uuid::new_v4();
+        let color = Self::color_for(&uuid);
+        let today = chrono::Utc::now().date_naive();
+        Self {
+            uuid,
+            name: "Bond".to_owned(),
+            face_value: 10000.0,
+            purchase_price: 10000.0,
+            coupon_rate: 3.0,
+            coupon_frequency: CouponFrequency::Annual,
+            acquisition_date: today,
+            maturity_date: today + Months::new(12 * 10),
+            payout_target: None,
+            should_delete: false,
+            color,
+            meta: AssetMeta::default(),
+        }
+    }
+}
+
+impl Bond {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        // create a purple nuanced color from uuid
+        egui::Color32::from_rgb(
+            get_random_bytes_from_uuid(uuid),
+            70,
+            get_random_bytes_from_uuid(uuid),
+        )
+    }
+
+    /// Dates of all coupons paid after acquisition, up to and including maturity.
+    /// The schedule is anchored on the maturity date and counted backwards.
+    pub fn coupon_dates(&self) -> Vec<NaiveDate> {
+        let months_between = 12 / self.coupon_frequency.payments_per_year();
+        let mut dates = Vec::new();
+        let mut periods = 0;
+        while let Some(coupon_date) = self
+            .maturity_date
+            .checked_sub_months(Months::new(months_between * periods))
+        {
+            if coupon_date <= self.acquisition_date {
+                break;
+            }
+            dates.push(coupon_date);
+            periods += 1;
+        }
+        dates.reverse();
+        dates
+    }
+
+    pub fn coupon_amount(&self) -> f32 {
+        self.face_value * self.coupon_rate
+            / 100.0
+            / self.coupon_frequency.payments_per_year() as f32
+    }
+
+    /// Market value of the bond itself, without any coupons or principal it has paid out.
+    pub fn price(&self, date: NaiveDate) -> f32 {
+        // The principal is paid out at maturity, after which the bond is gone.
+        if date >= self.maturity_date {
+            return 0.0;
+        }
+        if date <= self.acquisition_date {
+            return self.purchase_price;
+        }
+
+        // Pull the price linearly towards the face value as maturity approaches.
+        let total_days = (self.maturity_date - self.acquisition_date).num_days() as f32;
+        let days_elapsed = (date - self.acquisition_date).num_days() as f32;
+        self.purchase_price + (self.face_value - self.purchase_price) * days_elapsed / total_days
+    }
+}
+
+impl AssetTrait for Bond {
+    fn value(&self, date: NaiveDate) -> f32 {
+        // Without a cash asset to pay into, coupons and principal stay with the bond.
+        let kept: f32 = if self.payout_target.is_none() {
+            self.payouts(date).iter().map(|(_, amount)| amount).sum()
+        } else {
+            0.0
+        };
+        self.price(date) + kept
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn ui_edit(&mut self, ui: &mut Ui, currency: String) -> bool {
+        let mut modified = false;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                modified |= ui.text_edit_singleline(&mut self.name).changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Face Value: "));
+                modified |= ui
+                    .add(amount_drag_value(&mut self.face_value, &currency).speed(100.0))
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Purchase Price: "));
+                modified |= ui
+                    .add(amount_drag_value(&mut self.purchase_price, &currency).speed(100.0))
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Coupon Rate (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.coupon_rate)
+                            .speed(0.1)
+                            .range(RateSchedule::SAVINGS_RATES),
+                    )
+                    .changed();
+            });
+
+            // Coupon Frequency.
+            ui.horizontal(|ui| {
+                ui.label(tr("Frequency: "));
+                egui::ComboBox::from_id_salt(("coupon_frequency", self.uuid))
+                    .selected_text(tr(&format!("{:?}", self.coupon_frequency)))
+                    .show_ui(ui, |ui| {
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.coupon_frequency,
+                                CouponFrequency::Annual,
+                                tr("Annual"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.coupon_frequency,
+                                CouponFrequency::SemiAnnual,
+                                tr("Semi-Annual"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.coupon_frequency,
+                                CouponFrequency::Quarterly,
+                                tr("Quarterly"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.coupon_frequency,
+                                CouponFrequency::Monthly,
+                                tr("Monthly"),
+                            )
+                            .changed();
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Maturity Date: "));
+                modified |= date_edit(ui, &mut self.maturity_date);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Acquisition Date: "));
+                ui.label(self.acquisition_date.to_string());
+            });
+        });
+
+        modified
+    }
+
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn should_delete(&self) -> bool {
+        self.should_delete
+    }
+
+    fn color(&self) -> egui::Color32 {
+        self.color
+    }
+
+    fn is_growth(&self) -> bool {
+        true
+    }
+
+    fn payouts(&self, date: NaiveDate) -> Vec<(NaiveDate, f32)> {
+        let coupon = self.coupon_amount();
+        let mut payouts: Vec<(NaiveDate, f32)> = self
+            .coupon_dates()
+            .into_iter()
+            .take_while(|coupon_date| *coupon_date <= date)
+            .map(|coupon_date| (coupon_date, coupon))
+            .collect();
+        if date >= self.maturity_date && self.maturity_date > self.acquisition_date {
+            payouts.push((self.maturity_date, self.face_value));
+        }
+        payouts
+    }
+
+    fn payout_target(&self) -> Option<Uuid> {
+        self.payout_target
+    }
+
+    fn payout_target_mut(&mut self) -> Option<&mut Option<Uuid>> {
+        Some(&mut self.payout_target)
+    }
+}
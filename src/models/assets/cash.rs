@@ -2,7 +2,16 @@ pub use chrono::NaiveDate;
 use egui::Ui;
 use uuid::Uuid;
 
-use crate::{asset::AssetTrait, plot_utils::get_random_bytes_from_uuid};
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    ledger::{Ledger, LedgerEntry, LedgerEntryKind},
+    plot_utils::get_random_bytes_from_uuid,
+    snapshot::merge_snapshots,
+    statement::Statement,
+    widgets::{amount_drag_value, number_drag_value},
+    ContributionPlan, RateSchedule, Snapshot,
+};
 
 use super::tradable::ContributionFrequency;
 
@@ -16,17 +25,32 @@ pub struct Cash {
     pub contribution_frequency: ContributionFrequency,
     pub should_delete: bool,
     pub color: egui::Color32,
+    #[serde(default)]
+    pub contribution_plan: ContributionPlan,
+    #[serde(default)]
+    pub interest_rate: f32, // annual interest on a positive balance (%)
+    #[serde(default = "default_interest_frequency")]
+    pub interest_frequency: ContributionFrequency, // how often accrued interest is paid out
+    #[serde(default)]
+    pub overdraft_limit: f32, // how far below zero withdrawals may take the balance
+    #[serde(default)]
+    pub overdraft_rate: f32, // annual interest charged on a negative balance (%)
+    #[serde(default)]
+    pub balance_history: Vec<Snapshot>, // recorded balances, sorted by date
+    #[serde(default)]
+    pub ledger: Ledger, // recorded deposits, withdrawals and fees
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+fn default_interest_frequency() -> ContributionFrequency {
+    ContributionFrequency::Monthly
 }
 
 impl Default for Cash {
     fn default() -> Self {
         let uuid = Uuid::new_v4();
-        // create a red nuanced color from uuid
-        let color = egui::Color32::from_rgb(
-            70,
-            get_random_bytes_from_uuid(&uuid),
-            get_random_bytes_from_uuid(&uuid),
-        );
+        let color = Self::color_for(&uuid);
         Self {
             uuid,
             name: "Cash".to_owned(),
@@ -36,30 +60,151 @@ impl Default for Cash {
             contribution_frequency: ContributionFrequency::Monthly, // default frequency
             should_delete: false,
             color,
+            contribution_plan: ContributionPlan::default(),
+            interest_rate: 0.0,
+            interest_frequency: default_interest_frequency(),
+            overdraft_limit: 0.0,
+            overdraft_rate: 0.0,
+            balance_history: Vec::new(),
+            ledger: Ledger::default(),
+            meta: AssetMeta::default(),
+        }
+    }
+}
+
+impl Cash {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        // create a red nuanced color from uuid
+        egui::Color32::from_rgb(
+            70,
+            get_random_bytes_from_uuid(uuid),
+            get_random_bytes_from_uuid(uuid),
+        )
+    }
+
+    /// Balance recorded up to `date`: the last recorded balance on or before the date plus the
+    /// ledger entries since. Before the first recorded balance, the entries up to it are taken
+    /// back off it instead, and without any recorded balance the ledger adds to the initial
+    /// value.
+    pub fn recorded_value(&self, date: NaiveDate) -> f32 {
+        let last_balance = self
+            .balance_history
+            .iter()
+            .take_while(|snapshot| snapshot.date <= date)
+            .last();
+        match (last_balance, self.balance_history.first()) {
+            (Some(last), _) => last.value + self.ledger.change_between(last.date, date),
+            (None, Some(first)) => first.value - self.ledger.change_between(date, first.date),
+            (None, None) => self.value + self.ledger.change_until(date),
+        }
+    }
+
+    /// Date of the last recorded balance or ledger entry, after which the projection takes over.
+    pub fn history_end(&self) -> Option<NaiveDate> {
+        let last_balance = self.balance_history.last().map(|snapshot| snapshot.date);
+        last_balance.max(self.ledger.last_date())
+    }
+
+    /// Balance and date the projection starts from: the end of the recorded history if there
+    /// is one, otherwise the initial value at acquisition.
+    pub fn projection_start(&self) -> (f32, NaiveDate) {
+        match self.history_end() {
+            Some(end) => (self.recorded_value(end), end),
+            None => (self.value, self.acquisition_date),
+        }
+    }
+
+    /// Appends the statement's transactions to the ledger and records its closing balance.
+    /// Transactions already imported are skipped. Returns the number of added transactions.
+    pub fn import_statement(&mut self, statement: &Statement) -> usize {
+        let added = statement
+            .transactions
+            .iter()
+            .filter(|transaction| {
+                self.ledger
+                    .append(LedgerEntry::from_transaction(transaction))
+            })
+            .count();
+        if let Some(closing_balance) = &statement.closing_balance {
+            merge_snapshots(&mut self.balance_history, [closing_balance.clone()]);
         }
+        added
+    }
+
+    /// Interest accrued on a balance over the given number of days, using the overdraft rate
+    /// when the balance is negative.
+    fn accrued_interest(&self, balance: f32, days: i64) -> f32 {
+        let rate = if balance >= 0.0 {
+            self.interest_rate
+        } else {
+            self.overdraft_rate
+        };
+        balance * rate / 100.0 * days as f32 / 365.0
+    }
+
+    /// Adds a contribution to the balance. A withdrawal past the overdraft limit is only made
+    /// down to the limit, as the bank would refuse the rest. Overdraft interest may still take
+    /// the balance below the limit, and is never written off.
+    fn apply_contribution(&self, balance: f32, contribution: f32) -> f32 {
+        if contribution >= 0.0 {
+            return balance + contribution;
+        }
+        (balance + contribution).max(balance.min(-self.overdraft_limit))
     }
 }
 
 impl AssetTrait for Cash {
     fn value(&self, date: NaiveDate) -> f32 {
-        if date <= self.acquisition_date {
-            return self.value;
+        // Within the recorded history the value follows the recorded balances and ledger.
+        if self.history_end().is_some_and(|end| date <= end) {
+            return self.recorded_value(date);
         }
 
-        let duration = date.signed_duration_since(self.acquisition_date);
-        let total_days = duration.num_days() as f32;
+        let (start_value, start_date) = self.projection_start();
+        if date <= start_date {
+            return start_value;
+        }
 
-        let interval_days = match self.contribution_frequency {
-            ContributionFrequency::Weekly => 7.0,
-            ContributionFrequency::Monthly => 30.0,
-            ContributionFrequency::Yearly => 365.0,
+        let contribution_interval = self.contribution_frequency.interval_days();
+        let interest_interval = self.interest_frequency.interval_days();
+        let period_date = |interval: f32, index: i32| {
+            start_date + chrono::Duration::days((index as f32 * interval) as i64)
         };
 
-        let num_intervals = (total_days / interval_days).floor() as i32;
-        let mut current_value = self.value;
+        let mut current_value = start_value;
+        let mut accrued = 0.0;
+        let mut last_date = start_date;
+        let mut contribution_index = 1;
+        let mut interest_index = 1;
+
+        // Walk through contributions and interest payouts in date order. Interest accrues
+        // daily on the running balance and is credited on each payout date.
+        loop {
+            let contribution_date = period_date(contribution_interval, contribution_index);
+            let interest_date = period_date(interest_interval, interest_index);
+            let next_date = contribution_date.min(interest_date);
+            if next_date > date {
+                break;
+            }
 
-        for _ in 0..num_intervals {
-            current_value = (current_value + self.contribution).max(0.0);
+            accrued += self.accrued_interest(current_value, (next_date - last_date).num_days());
+            last_date = next_date;
+
+            if interest_date == next_date {
+                current_value += accrued;
+                accrued = 0.0;
+                interest_index += 1;
+            }
+            if contribution_date == next_date {
+                let contribution = self.contribution_plan.amount(
+                    self.contribution,
+                    self.acquisition_date,
+                    contribution_date,
+                );
+                current_value = self.apply_contribution(current_value, contribution);
+                contribution_index += 1;
+            }
         }
 
         current_value
@@ -80,55 +225,138 @@ impl AssetTrait for Cash {
 
             // Initial Value.
             ui.horizontal(|ui| {
-                ui.label("Value: ");
+                ui.label(tr("Value: "));
                 modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.value)
-                            .speed(1000.0)
-                            .prefix(currency.clone()),
-                    )
+                    .add(amount_drag_value(&mut self.value, &currency).speed(1000.0))
                     .changed();
             });
 
             // Contribution Amount.
             ui.horizontal(|ui| {
-                ui.label("Contribution: ");
+                ui.label(tr("Contribution: "));
                 modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.contribution)
-                            .speed(10.0)
-                            .prefix(currency.clone()),
-                    )
+                    .add(amount_drag_value(&mut self.contribution, &currency).speed(10.0))
                     .changed();
             });
 
             // Contribution Frequency.
             ui.horizontal(|ui| {
-                ui.label("Frequency: ");
+                ui.label(tr("Frequency: "));
                 egui::ComboBox::from_id_salt("tradable_frequency")
-                    .selected_text(format!("{:?}", self.contribution_frequency))
+                    .selected_text(tr(&format!("{:?}", self.contribution_frequency)))
                     .show_ui(ui, |ui| {
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Weekly,
-                            "Weekly",
+                            tr("Weekly"),
                         );
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Monthly,
-                            "Monthly",
+                            tr("Monthly"),
                         );
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Yearly,
-                            "Yearly",
+                            tr("Yearly"),
                         );
                     });
             });
 
+            // Interest.
+            ui.horizontal(|ui| {
+                ui.label(tr("Interest Rate (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.interest_rate)
+                            .speed(0.05)
+                            .range(RateSchedule::SAVINGS_RATES),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Interest Paid: "));
+                egui::ComboBox::from_id_salt(("cash_interest_frequency", self.uuid))
+                    .selected_text(tr(&format!("{:?}", self.interest_frequency)))
+                    .show_ui(ui, |ui| {
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.interest_frequency,
+                                ContributionFrequency::Weekly,
+                                tr("Weekly"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.interest_frequency,
+                                ContributionFrequency::Monthly,
+                                tr("Monthly"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.interest_frequency,
+                                ContributionFrequency::Yearly,
+                                tr("Yearly"),
+                            )
+                            .changed();
+                    });
+            });
+
+            // Overdraft.
+            ui.horizontal(|ui| {
+                ui.label(tr("Overdraft Limit: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.overdraft_limit, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            if self.overdraft_limit > 0.0 {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Overdraft Rate (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut self.overdraft_rate)
+                                .speed(0.1)
+                                .range(0.0..=50.0),
+                        )
+                        .changed();
+                });
+            }
+
+            // Escalation, end date and pauses.
+            ui.collapsing(tr("Contribution Plan"), |ui| {
+                modified |= self.contribution_plan.ui_edit(ui, self.acquisition_date);
+            });
+
+            // Recorded balances.
+            if let (Some(first), Some(last)) = (
+                self.balance_history.first().map(|snapshot| snapshot.date),
+                self.balance_history.last().map(|snapshot| snapshot.date),
+            ) {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} {} {}, {first} {} {last}",
+                        tr("History:"),
+                        self.balance_history.len(),
+                        tr("balances"),
+                        tr("to")
+                    ));
+                    if ui.button(tr("Clear")).clicked() {
+                        self.balance_history.clear();
+                        modified = true;
+                    }
+                });
+            }
+
             // Acquisition Date (display only for now).
             ui.horizontal(|ui| {
-                ui.label("Acquisition Date: ");
+                ui.label(tr("Acquisition Date: "));
                 ui.label(self.acquisition_date.to_string());
             });
         });
@@ -151,4 +379,82 @@ impl AssetTrait for Cash {
     fn is_growth(&self) -> bool {
         true
     }
+
+    fn history_start(&self) -> Option<NaiveDate> {
+        let first_balance = self.balance_history.first().map(|snapshot| snapshot.date);
+        match (first_balance, self.ledger.first_date()) {
+            (Some(balance), Some(entry)) => Some(balance.min(entry)),
+            (balance, entry) => balance.or(entry),
+        }
+    }
+
+    fn fees_paid(&self, date: NaiveDate) -> f32 {
+        self.ledger.total(LedgerEntryKind::Fee, date)
+    }
+
+    fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        Some(&mut self.ledger)
+    }
+    fn ledger_kinds(&self) -> &'static [LedgerEntryKind] {
+        &LedgerEntryKind::CASH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn stops_withdrawals_at_the_overdraft_limit() {
+        // Withdraws 100 every 30 days, with interest on the overdraft paid on the same days.
+        let cash = Cash {
+            value: 0.0,
+            acquisition_date: date(2024, 1, 1),
+            contribution: -100.0,
+            overdraft_limit: 250.0,
+            overdraft_rate: 12.0,
+            ..Cash::default()
+        };
+        let interest = |balance: f32| balance * 0.12 * 30.0 / 365.0;
+
+        assert_eq!(cash.value(date(2024, 1, 31)), -100.0);
+        let expected = -100.0 + interest(-100.0) - 100.0;
+        assert!((cash.value(date(2024, 3, 1)) - expected).abs() < 1e-3);
+
+        // The third withdrawal is only made down to the limit.
+        assert_eq!(cash.value(date(2024, 3, 31)), -250.0);
+
+        // Interest takes the balance past the limit, and the next withdrawal is refused.
+        let expected = -250.0 + interest(-250.0);
+        assert!((cash.value(date(2024, 4, 30)) - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn moves_the_balance_by_cash_entries() {
+        let mut cash = Cash {
+            value: 1000.0,
+            acquisition_date: date(2024, 1, 1),
+            ..Cash::default()
+        };
+        // Buying and selling securities is not offered on a cash account.
+        assert!(!cash.ledger_kinds().contains(&LedgerEntryKind::Buy));
+        assert!(!cash.ledger_kinds().contains(&LedgerEntryKind::Sell));
+
+        let signs = [1.0, -1.0, -1.0, 1.0];
+        for (&kind, sign) in cash.ledger_kinds().iter().zip(signs) {
+            let before = cash.value(date(2024, 1, 2));
+            cash.ledger.append(LedgerEntry {
+                date: date(2024, 1, 2),
+                kind,
+                amount: 100.0,
+                note: String::new(),
+                reference: None,
+            });
+            assert_eq!(cash.value(date(2024, 1, 2)) - before, sign * 100.0);
+        }
+    }
 }
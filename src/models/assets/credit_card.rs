@@ -0,0 +1,312 @@
+use chrono::Months;
+pub use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    format::format_amount,
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, number_drag_value},
+    RateSchedule,
+};
+
+/// Give up on paying off the balance after this many monthly statements.
+const MAX_STATEMENTS: u32 = 12 * 100;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CreditCard {
+    pub uuid: Uuid,
+    pub name: String,
+    pub value: f32,                 // carried balance
+    pub credit_limit: f32,          // interest never takes the balance past this
+    pub apr: f32,                   // annual percentage rate (%), compounded monthly
+    pub minimum_payment_rate: f32,  // share of the balance paid each month (%)
+    pub minimum_payment_floor: f32, // smallest minimum payment
+    pub extra_payment: f32,         // paid on top of the minimum each month
+    pub acquisition_date: NaiveDate,
+    pub should_delete: bool,
+    pub color: egui::Color32,
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+impl Default for CreditCard {
+    fn default() -> Self {
+        let uuid = Uuid::new_v4();
+        let color = Self::color_for(&uuid);
+        Self {
+            uuid,
+            name: "Credit Card".to_owned(),
+            value: 20000.0,
+            credit_limit: 50000.0,
+            apr: 20.0,
+            minimum_payment_rate: 3.0,
+            minimum_payment_floor: 200.0,
+            extra_payment: 0.0,
+            acquisition_date: chrono::Utc::now().date_naive(),
+            should_delete: false,
+            color,
+            meta: AssetMeta::default(),
+        }
+    }
+}
+
+impl CreditCard {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        egui::Color32::from_rgb(get_random_bytes_from_uuid(uuid), 40, 110)
+    }
+
+    /// Payment due on a statement with the given balance (interest included).
+    fn payment(&self, balance: f32) -> f32 {
+        let minimum = (balance * self.minimum_payment_rate / 100.0).max(self.minimum_payment_floor);
+        (minimum + self.extra_payment).min(balance)
+    }
+
+    /// Interest charged on a statement with the given balance, capped so that it never takes
+    /// the balance past the credit limit. A balance already over the limit is charged none.
+    fn interest(&self, balance: f32) -> f32 {
+        let monthly_rate = self.apr / 100.0 / 12.0;
+        (balance * monthly_rate).min((self.credit_limit - balance).max(0.0))
+    }
+
+    /// Runs the given number of monthly statements and returns the remaining balance and the
+    /// interest charged along the way.
+    fn simulate(&self, statements: u32) -> (f32, f32) {
+        let mut balance = self.value;
+        let mut interest_paid = 0.0;
+        for _ in 0..statements {
+            if balance <= 0.0 {
+                break;
+            }
+            let interest = self.interest(balance);
+            interest_paid += interest;
+            balance += interest;
+            balance -= self.payment(balance);
+        }
+        (balance.max(0.0), interest_paid)
+    }
+
+    /// Number of monthly statements until the balance is cleared, or `None` if the payments
+    /// never catch up with the interest.
+    pub fn statements_to_payoff(&self) -> Option<u32> {
+        let mut balance = self.value;
+        for statement in 0..MAX_STATEMENTS {
+            if balance <= 0.0 {
+                return Some(statement);
+            }
+            balance += self.interest(balance);
+            let payment = self.payment(balance);
+            if payment <= 0.0 {
+                return None;
+            }
+            balance -= payment;
+        }
+        None
+    }
+
+    pub fn payoff_date(&self) -> Option<NaiveDate> {
+        self.statements_to_payoff().and_then(|statements| {
+            self.acquisition_date
+                .checked_add_months(Months::new(statements))
+        })
+    }
+
+    /// Total interest charged until the balance is cleared.
+    pub fn total_interest(&self) -> f32 {
+        self.simulate(MAX_STATEMENTS).1
+    }
+}
+
+impl AssetTrait for CreditCard {
+    fn value(&self, date: NaiveDate) -> f32 {
+        if date <= self.acquisition_date {
+            return self.value;
+        }
+
+        // Interest is charged and payments are made once per monthly statement.
+        let mut statements = 0;
+        while statements < MAX_STATEMENTS
+            && self
+                .acquisition_date
+                .checked_add_months(Months::new(statements + 1))
+                .is_some_and(|statement_date| statement_date <= date)
+        {
+            statements += 1;
+        }
+
+        self.simulate(statements).0
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn ui_edit(&mut self, ui: &mut Ui, currency: String) -> bool {
+        let mut modified = false;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                modified |= ui.text_edit_singleline(&mut self.name).changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Credit Limit: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.credit_limit, &currency)
+                            .speed(1000.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Balance: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.value, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("APR (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.apr)
+                            .speed(0.1)
+                            .range(RateSchedule::LOAN_RATES),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Minimum Payment (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.minimum_payment_rate)
+                            .speed(0.1)
+                            .range(0.0..=100.0),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Minimum Payment Floor: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.minimum_payment_floor, &currency)
+                            .speed(10.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Extra Payment: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.extra_payment, &currency)
+                            .speed(10.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            // Payoff summary.
+            ui.horizontal(|ui| {
+                ui.label(tr("Paid Off: "));
+                match self.payoff_date() {
+                    Some(payoff_date) => ui.label(payoff_date.to_string()),
+                    None => ui.label(tr("Never")),
+                };
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Total Interest: "));
+                ui.label(format_amount(self.total_interest(), &currency));
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Acquisition Date: "));
+                ui.label(self.acquisition_date.to_string());
+            });
+        });
+
+        modified
+    }
+
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn should_delete(&self) -> bool {
+        self.should_delete
+    }
+
+    fn color(&self) -> egui::Color32 {
+        self.color
+    }
+
+    fn is_growth(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn card(value: f32, apr: f32, extra_payment: f32) -> CreditCard {
+        CreditCard {
+            value,
+            credit_limit: 10000.0,
+            apr,
+            minimum_payment_rate: 0.0,
+            minimum_payment_floor: 0.0,
+            extra_payment,
+            acquisition_date: date(2024, 1, 15),
+            ..CreditCard::default()
+        }
+    }
+
+    #[test]
+    fn pays_off_with_monthly_interest() {
+        let free = card(1000.0, 0.0, 100.0);
+        assert_eq!(free.statements_to_payoff(), Some(10));
+        assert_eq!(free.total_interest(), 0.0);
+        assert_eq!(free.payoff_date(), Some(date(2024, 11, 15)));
+
+        // 1% a month on the balance before each payment.
+        let charged = card(1000.0, 12.0, 100.0);
+        assert_eq!(charged.value(date(2024, 2, 15)), 910.0);
+        assert_eq!(charged.statements_to_payoff(), Some(11));
+        assert!((charged.total_interest() - 58.985).abs() < 0.01);
+
+        // Payments that never catch up with the interest.
+        assert_eq!(card(5000.0, 24.0, 100.0).statements_to_payoff(), None);
+    }
+
+    #[test]
+    fn keeps_a_balance_over_the_limit() {
+        let card = card(12000.0, 24.0, 500.0);
+        assert_eq!(card.value(card.acquisition_date), 12000.0);
+
+        // No interest is charged until payments bring the balance within the limit.
+        assert_eq!(card.value(date(2024, 2, 15)), 11500.0);
+        assert_eq!(card.value(date(2024, 5, 15)), 10000.0);
+        assert_eq!(card.value(date(2024, 6, 15)), 9500.0);
+        assert_eq!(card.value(date(2024, 7, 15)), 9500.0 * 1.02 - 500.0);
+
+        assert_eq!(card.statements_to_payoff(), Some(30));
+        assert!((card.total_interest() - 2570.61).abs() < 0.5);
+    }
+}
@@ -0,0 +1,306 @@
+pub use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, date_edit, number_drag_value},
+    RateSchedule,
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum DepreciationModel {
+    /// Loses the same amount every year until the residual value is reached at the end of the useful life.
+    StraightLine,
+    /// Loses a fixed percentage of the remaining value every year, never dropping below the residual value.
+    DecliningBalance,
+    /// Loses a constant percentage per year chosen so that the residual value is reached at the end of the useful life.
+    FixedResidual,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Depreciating {
+    pub uuid: Uuid,
+    pub name: String,
+    pub purchase_price: f32,
+    pub acquisition_date: NaiveDate,
+    pub depreciation_model: DepreciationModel,
+    pub depreciation_rate: f32, // annual depreciation (%), used by declining balance
+    pub useful_life_years: f32,
+    pub residual_value: f32,
+    pub sale_date: Option<NaiveDate>,
+    pub should_delete: bool,
+    pub color: egui::Color32,
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+impl Default for Depreciating {
+    fn default() -> Self {
+        let uuid = Uuid::new_v4();
+        let color = Self::color_for(&uuid);
+        Self {
+            uuid,
+            name: "Vehicle".to_owned(),
+            purchase_price: 300000.0,
+            acquisition_date: chrono::Utc::now().date_naive(),
+            depreciation_model: DepreciationModel::DecliningBalance,
+            depreciation_rate: 15.0,
+            useful_life_years: 10.0,
+            residual_value: 20000.0,
+            sale_date: None,
+            should_delete: false,
+            color,
+            meta: AssetMeta::default(),
+        }
+    }
+}
+
+impl Depreciating {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        // create a yellow nuanced color from uuid
+        egui::Color32::from_rgb(
+            get_random_bytes_from_uuid(uuid),
+            get_random_bytes_from_uuid(uuid),
+            70,
+        )
+    }
+}
+
+impl AssetTrait for Depreciating {
+    fn value(&self, date: NaiveDate) -> f32 {
+        // Once sold the asset no longer contributes to the portfolio.
+        if let Some(sale_date) = self.sale_date {
+            if date >= sale_date {
+                return 0.0;
+            }
+        }
+        if date <= self.acquisition_date {
+            return self.purchase_price;
+        }
+
+        let duration = date.signed_duration_since(self.acquisition_date);
+        let years_elapsed = duration.num_days() as f32 / 365.0;
+        let residual = self.residual_value.clamp(0.0, self.purchase_price.max(0.0));
+
+        match self.depreciation_model {
+            DepreciationModel::StraightLine => {
+                if self.useful_life_years <= 0.0 {
+                    return residual;
+                }
+                let depreciation_per_year =
+                    (self.purchase_price - residual) / self.useful_life_years;
+                (self.purchase_price - depreciation_per_year * years_elapsed).max(residual)
+            }
+            DepreciationModel::DecliningBalance => {
+                let remaining_fraction = (1.0 - self.depreciation_rate / 100.0)
+                    .max(0.0)
+                    .powf(years_elapsed);
+                (self.purchase_price * remaining_fraction).max(residual)
+            }
+            DepreciationModel::FixedResidual => {
+                if self.useful_life_years <= 0.0 || self.purchase_price <= 0.0 {
+                    return residual;
+                }
+                if years_elapsed >= self.useful_life_years {
+                    return residual;
+                }
+                // A zero residual would make the asset worthless immediately, so use at least 1.
+                let residual_fraction =
+                    residual.max(1.0).min(self.purchase_price) / self.purchase_price;
+                self.purchase_price * residual_fraction.powf(years_elapsed / self.useful_life_years)
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn ui_edit(&mut self, ui: &mut Ui, currency: String) -> bool {
+        let mut modified = false;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                modified |= ui.text_edit_singleline(&mut self.name).changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Purchase Price: "));
+                modified |= ui
+                    .add(amount_drag_value(&mut self.purchase_price, &currency).speed(1000.0))
+                    .changed();
+            });
+
+            // Depreciation Model.
+            ui.horizontal(|ui| {
+                ui.label(tr("Model: "));
+                egui::ComboBox::from_id_salt(("depreciation_model", self.uuid))
+                    .selected_text(tr(&format!("{:?}", self.depreciation_model)))
+                    .show_ui(ui, |ui| {
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.depreciation_model,
+                                DepreciationModel::StraightLine,
+                                tr("Straight Line"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.depreciation_model,
+                                DepreciationModel::DecliningBalance,
+                                tr("Declining Balance"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.depreciation_model,
+                                DepreciationModel::FixedResidual,
+                                tr("Fixed Residual"),
+                            )
+                            .changed();
+                    });
+            });
+
+            if self.depreciation_model == DepreciationModel::DecliningBalance {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Depreciation Rate (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut self.depreciation_rate)
+                                .speed(0.1)
+                                .range(RateSchedule::DEPRECIATION_RATES),
+                        )
+                        .changed();
+                });
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Useful Life (years): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut self.useful_life_years)
+                                .speed(0.1)
+                                .range(0.0..=100.0),
+                        )
+                        .changed();
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Residual Value: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.residual_value, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            // Optional sale date.
+            ui.horizontal(|ui| {
+                let mut sold = self.sale_date.is_some();
+                if ui.checkbox(&mut sold, tr("Sale Date: ")).changed() {
+                    self.sale_date = sold.then(|| {
+                        self.acquisition_date
+                            + chrono::Duration::days((self.useful_life_years * 365.0) as i64)
+                    });
+                    modified = true;
+                }
+                if let Some(sale_date) = &mut self.sale_date {
+                    modified |= date_edit(ui, sale_date);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Acquisition Date: "));
+                ui.label(self.acquisition_date.to_string());
+            });
+        });
+
+        modified
+    }
+
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn should_delete(&self) -> bool {
+        self.should_delete
+    }
+
+    fn color(&self) -> egui::Color32 {
+        self.color
+    }
+
+    fn is_growth(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn asset(depreciation_model: DepreciationModel) -> Depreciating {
+        Depreciating {
+            purchase_price: 10000.0,
+            acquisition_date: date(2023, 1, 1),
+            depreciation_model,
+            depreciation_rate: 20.0,
+            useful_life_years: 5.0,
+            residual_value: 1000.0,
+            ..Depreciating::default()
+        }
+    }
+
+    /// The date the given number of 365 day years after acquisition.
+    fn after_years(asset: &Depreciating, years: u64) -> NaiveDate {
+        asset.acquisition_date + Days::new(years * 365)
+    }
+
+    fn assert_value(asset: &Depreciating, years: u64, expected: f32) {
+        let value = asset.value(after_years(asset, years));
+        assert!((value - expected).abs() < 0.1, "{value} != {expected}");
+    }
+
+    #[test]
+    fn depreciates_in_a_straight_line() {
+        let asset = asset(DepreciationModel::StraightLine);
+        assert_eq!(asset.value(date(2022, 6, 1)), 10000.0);
+        assert_value(&asset, 1, 8200.0);
+        assert_value(&asset, 3, 4600.0);
+        assert_value(&asset, 5, 1000.0);
+        assert_value(&asset, 8, 1000.0);
+    }
+
+    #[test]
+    fn depreciates_a_declining_balance() {
+        let asset = asset(DepreciationModel::DecliningBalance);
+        assert_eq!(asset.value(date(2022, 6, 1)), 10000.0);
+        assert_value(&asset, 1, 8000.0);
+        assert_value(&asset, 2, 6400.0);
+        // 0.8^20 of the price is far below the residual value.
+        assert_value(&asset, 20, 1000.0);
+    }
+
+    #[test]
+    fn reaches_the_residual_value_at_the_end_of_its_life() {
+        let mut asset = asset(DepreciationModel::FixedResidual);
+        assert_eq!(asset.value(date(2022, 6, 1)), 10000.0);
+        assert_value(&asset, 2, 10000.0 * 0.1f32.powf(0.4));
+        assert_value(&asset, 5, 1000.0);
+        assert_value(&asset, 8, 1000.0);
+
+        asset.sale_date = Some(date(2025, 1, 1));
+        assert_eq!(asset.value(date(2025, 1, 1)), 0.0);
+    }
+}
@@ -0,0 +1,388 @@
+use chrono::Months;
+pub use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, number_drag_value},
+    RateSchedule,
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum GrantType {
+    Rsu,
+    StockOption,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum VestingFrequency {
+    Monthly,
+    Quarterly,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct EquityGrant {
+    pub uuid: Uuid,
+    pub name: String,
+    pub grant_type: GrantType,
+    pub share_count: f32,
+    pub share_price: f32,            // price per share on the grant date
+    pub strike_price: f32,           // exercise price, only used by stock options
+    pub price_growth_rate: f32,      // projected annual share price growth (%)
+    pub acquisition_date: NaiveDate, // grant date
+    pub cliff_months: u32,
+    pub vesting_months: u32, // total length of the vesting schedule
+    pub vesting_frequency: VestingFrequency,
+    pub tax_rate: f32, // share of each vest sold to cover the tax (%)
+    pub should_delete: bool,
+    pub color: egui::Color32,
+    #[serde(default)]
+    pub rate_schedule: Option<RateSchedule>, // share price growth that changes over time
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+impl Default for EquityGrant {
+    fn default() -> Self {
+        let uuid = Uuid::new_v4();
+        let color = Self::color_for(&uuid);
+        Self {
+            uuid,
+            name: "RSU Grant".to_owned(),
+            grant_type: GrantType::Rsu,
+            share_count: 1000.0,
+            share_price: 100.0,
+            strike_price: 0.0,
+            price_growth_rate: 8.0,
+            acquisition_date: chrono::Utc::now().date_naive(),
+            cliff_months: 12,
+            vesting_months: 48,
+            vesting_frequency: VestingFrequency::Monthly,
+            tax_rate: 50.0,
+            should_delete: false,
+            color,
+            rate_schedule: None,
+            meta: AssetMeta::default(),
+        }
+    }
+}
+
+impl EquityGrant {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        egui::Color32::from_rgb(40, get_random_bytes_from_uuid(uuid), 200)
+    }
+
+    /// Projected share price on the given date.
+    pub fn share_price_at(&self, date: NaiveDate) -> f32 {
+        self.share_price
+            * RateSchedule::optional_growth_multiplier(
+                &self.rate_schedule,
+                self.price_growth_rate,
+                self.acquisition_date,
+                date,
+            )
+    }
+
+    /// Whole months from the grant date up to the given date, at most the vesting period.
+    fn months_elapsed(&self, date: NaiveDate) -> u32 {
+        let mut months_elapsed = 0;
+        while months_elapsed < self.vesting_months
+            && self
+                .acquisition_date
+                .checked_add_months(Months::new(months_elapsed + 1))
+                .is_some_and(|month| month <= date)
+        {
+            months_elapsed += 1;
+        }
+        months_elapsed
+    }
+
+    /// Months of the vesting period that have vested after the given number of months.
+    /// Nothing vests before the cliff, at which point everything accrued so far vests at once.
+    fn vested_months(&self, months_elapsed: u32) -> u32 {
+        if months_elapsed < self.cliff_months.min(self.vesting_months) {
+            return 0;
+        }
+        let months_per_vest = match self.vesting_frequency {
+            VestingFrequency::Monthly => 1,
+            VestingFrequency::Quarterly => 3,
+        };
+        if months_elapsed == self.vesting_months {
+            self.vesting_months
+        } else {
+            months_elapsed / months_per_vest * months_per_vest
+        }
+    }
+
+    /// Number of shares vested on the given date.
+    pub fn vested_shares(&self, date: NaiveDate) -> f32 {
+        if self.vesting_months == 0 {
+            return self.share_count;
+        }
+        self.share_count * self.vested_months(self.months_elapsed(date)) as f32
+            / self.vesting_months as f32
+    }
+
+    pub fn unvested_shares(&self, date: NaiveDate) -> f32 {
+        self.share_count - self.vested_shares(date)
+    }
+
+    /// Value of a single share before tax on the given date.
+    fn value_per_share(&self, date: NaiveDate) -> f32 {
+        match self.grant_type {
+            GrantType::Rsu => self.share_price_at(date),
+            GrantType::StockOption => (self.share_price_at(date) - self.strike_price).max(0.0),
+        }
+    }
+}
+
+impl AssetTrait for EquityGrant {
+    fn value(&self, date: NaiveDate) -> f32 {
+        // Shares are withheld at each vest to cover the tax, and the rest are kept.
+        self.vested_shares(date) * (1.0 - self.tax_rate / 100.0) * self.value_per_share(date)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn ui_edit(&mut self, ui: &mut Ui, currency: String) -> bool {
+        let mut modified = false;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                modified |= ui.text_edit_singleline(&mut self.name).changed();
+            });
+
+            // Grant Type.
+            ui.horizontal(|ui| {
+                ui.label(tr("Type: "));
+                egui::ComboBox::from_id_salt(("grant_type", self.uuid))
+                    .selected_text(tr(&format!("{:?}", self.grant_type)))
+                    .show_ui(ui, |ui| {
+                        modified |= ui
+                            .selectable_value(&mut self.grant_type, GrantType::Rsu, tr("RSU"))
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.grant_type,
+                                GrantType::StockOption,
+                                tr("Stock Option"),
+                            )
+                            .changed();
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Shares: "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.share_count)
+                            .speed(10.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Share Price: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.share_price, &currency)
+                            .speed(1.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            if self.grant_type == GrantType::StockOption {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Strike Price: "));
+                    modified |= ui
+                        .add(
+                            amount_drag_value(&mut self.strike_price, &currency)
+                                .speed(1.0)
+                                .range(0.0..=f32::MAX),
+                        )
+                        .changed();
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Price Growth (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.price_growth_rate)
+                            .speed(0.1)
+                            .range(RateSchedule::GROWTH_RATES),
+                    )
+                    .changed();
+            });
+
+            // Variable rate schedule.
+            modified |= RateSchedule::optional_ui_edit(
+                ui,
+                ("equity_grant_rate_schedule", self.uuid),
+                &mut self.rate_schedule,
+                self.price_growth_rate,
+                self.acquisition_date,
+                RateSchedule::GROWTH_RATES,
+            );
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Cliff (months): "));
+                modified |= ui
+                    .add(number_drag_value(&mut self.cliff_months).range(0..=self.vesting_months))
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Vesting (months): "));
+                if ui
+                    .add(number_drag_value(&mut self.vesting_months).range(0..=240))
+                    .changed()
+                {
+                    // The cliff cannot be longer than the vesting period.
+                    self.cliff_months = self.cliff_months.min(self.vesting_months);
+                    modified = true;
+                }
+            });
+
+            // Vesting Frequency.
+            ui.horizontal(|ui| {
+                ui.label(tr("Frequency: "));
+                egui::ComboBox::from_id_salt(("vesting_frequency", self.uuid))
+                    .selected_text(tr(&format!("{:?}", self.vesting_frequency)))
+                    .show_ui(ui, |ui| {
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.vesting_frequency,
+                                VestingFrequency::Monthly,
+                                tr("Monthly"),
+                            )
+                            .changed();
+                        modified |= ui
+                            .selectable_value(
+                                &mut self.vesting_frequency,
+                                VestingFrequency::Quarterly,
+                                tr("Quarterly"),
+                            )
+                            .changed();
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Tax at Vest (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.tax_rate)
+                            .speed(0.1)
+                            .range(0.0..=100.0),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Grant Date: "));
+                ui.label(self.acquisition_date.to_string());
+            });
+        });
+
+        modified
+    }
+
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn should_delete(&self) -> bool {
+        self.should_delete
+    }
+
+    fn color(&self) -> egui::Color32 {
+        self.color
+    }
+
+    fn is_growth(&self) -> bool {
+        true
+    }
+
+    fn unvested_value(&self, date: NaiveDate) -> f32 {
+        self.unvested_shares(date) * (1.0 - self.tax_rate / 100.0) * self.value_per_share(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn grant(cliff_months: u32, vesting_frequency: VestingFrequency) -> EquityGrant {
+        EquityGrant {
+            share_count: 1200.0,
+            share_price: 10.0,
+            price_growth_rate: 0.0,
+            acquisition_date: date(2024, 1, 1),
+            cliff_months,
+            vesting_months: 48,
+            vesting_frequency,
+            tax_rate: 50.0,
+            ..EquityGrant::default()
+        }
+    }
+
+    #[test]
+    fn vests_everything_accrued_at_the_cliff() {
+        let grant = grant(12, VestingFrequency::Monthly);
+        assert_eq!(grant.vested_shares(date(2024, 12, 31)), 0.0);
+        assert_eq!(grant.value(date(2024, 12, 31)), 0.0);
+
+        // A year of the four year schedule, half of it sold to cover the tax.
+        assert_eq!(grant.vested_shares(date(2025, 1, 1)), 300.0);
+        assert_eq!(grant.value(date(2025, 1, 1)), 1500.0);
+        assert_eq!(grant.vested_shares(date(2025, 2, 1)), 325.0);
+    }
+
+    #[test]
+    fn vests_quarterly() {
+        let grant = grant(0, VestingFrequency::Quarterly);
+        assert_eq!(grant.vested_shares(date(2024, 3, 31)), 0.0);
+        assert_eq!(grant.vested_shares(date(2024, 4, 1)), 75.0);
+        assert_eq!(grant.vested_shares(date(2024, 6, 30)), 75.0);
+        assert_eq!(grant.vested_shares(date(2024, 7, 1)), 150.0);
+    }
+
+    #[test]
+    fn vests_fully_at_the_end() {
+        let grant = grant(12, VestingFrequency::Quarterly);
+        assert_eq!(grant.vested_shares(date(2028, 1, 1)), 1200.0);
+        assert_eq!(grant.value(date(2028, 1, 1)), 6000.0);
+        assert_eq!(grant.value(date(2030, 1, 1)), 6000.0);
+        assert_eq!(grant.unvested_value(date(2030, 1, 1)), 0.0);
+    }
+
+    #[test]
+    fn never_goes_negative_with_a_falling_price() {
+        let mut grant = grant(0, VestingFrequency::Monthly);
+        grant.price_growth_rate = -20.0;
+        let mut previous = grant.value(date(2028, 1, 1));
+        assert!(previous > 0.0);
+        for year in 2029..2060 {
+            let value = grant.value(date(year, 1, 1));
+            assert!(0.0 < value && value < previous);
+            previous = value;
+        }
+
+        // Options under water are worth nothing rather than less.
+        grant.grant_type = GrantType::StockOption;
+        grant.strike_price = 15.0;
+        assert_eq!(grant.value(date(2028, 1, 1)), 0.0);
+    }
+}
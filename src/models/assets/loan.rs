@@ -1,8 +1,15 @@
 pub use chrono::NaiveDate;
+use chrono::{Days, Months};
 use egui::Ui;
 use uuid::Uuid;
 
-use crate::{asset::AssetTrait, plot_utils::get_random_bytes_from_uuid};
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, number_drag_value},
+    RateSchedule,
+};
 
 use super::tradable::ContributionFrequency;
 
@@ -17,11 +24,15 @@ pub struct Loan {
     pub principal_frequency: ContributionFrequency, // New field
     pub should_delete: bool,
     pub color: egui::Color32,
+    #[serde(default)]
+    pub rate_schedule: Option<RateSchedule>, // floating interest, if the rate is not fixed
+    #[serde(default)]
+    pub meta: AssetMeta,
 }
 impl Default for Loan {
     fn default() -> Self {
         let uuid = Uuid::new_v4();
-        let color = egui::Color32::from_rgb(get_random_bytes_from_uuid(&uuid), 70, 70);
+        let color = Self::color_for(&uuid);
         Self {
             uuid,
             name: "New Loan".to_owned(),
@@ -32,48 +43,112 @@ impl Default for Loan {
             color,
             principal_payment: 0.0,
             principal_frequency: ContributionFrequency::Monthly,
+            rate_schedule: None,
+            meta: AssetMeta::default(),
         }
     }
 }
 
-impl AssetTrait for Loan {
-    fn value(&self, date: NaiveDate) -> f32 {
-        if date <= self.acquisition_date {
-            return self.value;
-        }
+impl Loan {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        egui::Color32::from_rgb(get_random_bytes_from_uuid(uuid), 70, 70)
+    }
+
+    /// The annual interest rate (%) in effect on the given date.
+    pub fn rate_at(&self, date: NaiveDate) -> f32 {
+        RateSchedule::optional_rate_at(&self.rate_schedule, self.rate_per_year, date)
+    }
 
-        let duration = date.signed_duration_since(self.acquisition_date);
-        let total_days = duration.num_days() as f32;
+    /// Principal paid from acquisition up to the given date.
+    pub fn principal_paid(&self, date: NaiveDate) -> f32 {
+        self.amortize(date).1
+    }
 
-        // Determine the interval length and corresponding rate per interval.
-        let interval_days = match self.principal_frequency {
-            ContributionFrequency::Weekly => 7.0,
-            ContributionFrequency::Monthly => 30.0,
-            ContributionFrequency::Yearly => 365.0,
-        };
+    /// Interest charged between two dates: the change in balance plus what was paid off.
+    pub fn interest_between(&self, start: NaiveDate, end: NaiveDate) -> f32 {
+        let (start_principal, start_paid) = self.amortize(start);
+        let (end_principal, end_paid) = self.amortize(end);
+        end_principal - start_principal + end_paid - start_paid
+    }
 
-        let rate_per_interval =
-            (1.0 + self.rate_per_year / 100.0).powf(interval_days / 365.0) - 1.0;
-        let num_intervals = (total_days / interval_days).floor() as i32;
-        let remaining_days = total_days % interval_days;
+    /// The date of the payment that clears the loan, if that happens within a hundred years.
+    pub fn payoff_date(&self) -> Option<NaiveDate> {
+        let horizon = self
+            .acquisition_date
+            .checked_add_months(Months::new(1200))?;
+        self.payments()
+            .take_while(|(payment_date, _, _)| *payment_date <= horizon)
+            .find(|(_, remaining_principal, _)| *remaining_principal <= 0.0)
+            .map(|(payment_date, _, _)| payment_date)
+    }
 
-        // Start with the initial principal.
+    /// The payments in date order as (payment date, remaining principal, total principal
+    /// paid). Each interval accrues interest at the rate in effect at its start and ends with
+    /// a payment.
+    fn payments(&self) -> impl Iterator<Item = (NaiveDate, f32, f32)> + '_ {
         let mut remaining_principal = self.value;
-
-        // For each full interval:
-        for _ in 0..num_intervals {
+        let mut paid = 0.0;
+        (0..).map(move |i: u32| {
+            let interval_start = self.interval_date(i);
+            let interval_end = self.interval_date(i + 1);
+            let interval_days = (interval_end - interval_start).num_days() as f32;
+            let rate_per_interval =
+                (1.0 + self.rate_at(interval_start) / 100.0).powf(interval_days / 365.0) - 1.0;
             remaining_principal += remaining_principal * rate_per_interval;
-            remaining_principal = (remaining_principal - self.principal_payment).max(0.0);
+            let payment = self.principal_payment.min(remaining_principal);
+            remaining_principal -= payment;
+            paid += payment;
+            (interval_end, remaining_principal, paid)
+        })
+    }
+
+    /// Start of the interval with the given index, counted from acquisition. Monthly and
+    /// yearly payments fall on the same day of the month as the acquisition, or on the last
+    /// day of shorter months.
+    fn interval_date(&self, index: u32) -> NaiveDate {
+        let date = match self.principal_frequency {
+            ContributionFrequency::Weekly => self
+                .acquisition_date
+                .checked_add_days(Days::new(7 * index as u64)),
+            ContributionFrequency::Monthly => {
+                self.acquisition_date.checked_add_months(Months::new(index))
+            }
+            ContributionFrequency::Yearly => self
+                .acquisition_date
+                .checked_add_months(Months::new(12 * index)),
+        };
+        date.unwrap_or(NaiveDate::MAX)
+    }
+
+    /// Remaining principal and total principal paid on the given date.
+    fn amortize(&self, date: NaiveDate) -> (f32, f32) {
+        if date <= self.acquisition_date {
+            return (self.value, 0.0);
         }
 
-        // Handle any remaining partial interval.
+        // The payments made up to the date, starting from the initial principal.
+        let (last_payment_date, mut remaining_principal, paid) = self
+            .payments()
+            .take_while(|(payment_date, _, _)| *payment_date <= date)
+            .last()
+            .unwrap_or((self.acquisition_date, self.value, 0.0));
+
+        // Interest on the partial interval since the last payment.
+        let remaining_days = (date - last_payment_date).num_days() as f32;
         if remaining_days > 0.0 && remaining_principal > 0.0 {
             let fractional_rate =
-                (1.0 + self.rate_per_year / 100.0).powf((remaining_days) / 365.0) - 1.0;
+                (1.0 + self.rate_at(last_payment_date) / 100.0).powf(remaining_days / 365.0) - 1.0;
             remaining_principal += remaining_principal * fractional_rate;
         }
 
-        remaining_principal
+        (remaining_principal, paid)
+    }
+}
+
+impl AssetTrait for Loan {
+    fn value(&self, date: NaiveDate) -> f32 {
+        self.amortize(date).0
     }
 
     fn name(&self) -> String {
@@ -89,63 +164,65 @@ impl AssetTrait for Loan {
             });
 
             ui.horizontal(|ui| {
-                ui.label("Value: ");
+                ui.label(tr("Value: "));
                 modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.value)
-                            .speed(1000.0)
-                            .prefix(currency.clone()),
-                    )
+                    .add(amount_drag_value(&mut self.value, &currency).speed(1000.0))
                     .changed();
             });
 
             ui.horizontal(|ui| {
-                ui.label("Interest Rate (%): ");
+                ui.label(tr("Interest Rate (%): "));
                 modified |= ui
                     .add(
-                        egui::DragValue::new(&mut self.rate_per_year)
+                        number_drag_value(&mut self.rate_per_year)
                             .speed(0.1)
-                            .range(0.0..=20.0),
+                            .range(RateSchedule::LOAN_RATES),
                     )
                     .changed();
             });
 
+            // Variable rate schedule.
+            modified |= RateSchedule::optional_ui_edit(
+                ui,
+                ("loan_rate_schedule", self.uuid),
+                &mut self.rate_schedule,
+                self.rate_per_year,
+                self.acquisition_date,
+                RateSchedule::LOAN_RATES,
+            );
+
             ui.horizontal(|ui| {
-                ui.label("Principal: ");
+                ui.label(tr("Principal: "));
                 modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.principal_payment)
-                            .speed(100.0)
-                            .prefix(currency),
-                    )
+                    .add(amount_drag_value(&mut self.principal_payment, &currency).speed(100.0))
                     .changed();
             });
             // Contribution Frequency.
             ui.horizontal(|ui| {
-                ui.label("Frequency: ");
+                ui.label(tr("Frequency: "));
                 egui::ComboBox::from_id_salt("loan_frequency")
-                    .selected_text(format!("{:?}", self.principal_frequency))
+                    .selected_text(tr(&format!("{:?}", self.principal_frequency)))
                     .show_ui(ui, |ui| {
                         ui.selectable_value(
                             &mut self.principal_frequency,
                             ContributionFrequency::Weekly,
-                            "Weekly",
+                            tr("Weekly"),
                         );
                         ui.selectable_value(
                             &mut self.principal_frequency,
                             ContributionFrequency::Monthly,
-                            "Monthly",
+                            tr("Monthly"),
                         );
                         ui.selectable_value(
                             &mut self.principal_frequency,
                             ContributionFrequency::Yearly,
-                            "Yearly",
+                            tr("Yearly"),
                         );
                     });
             });
 
             ui.horizontal(|ui| {
-                ui.label("Acquisition Date: ");
+                ui.label(tr("Acquisition Date: "));
                 ui.label(self.acquisition_date.to_string());
             });
         });
@@ -169,3 +246,82 @@ impl AssetTrait for Loan {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_schedule::RateChange;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn loan(
+        value: f32,
+        rate_per_year: f32,
+        payment: f32,
+        frequency: ContributionFrequency,
+    ) -> Loan {
+        Loan {
+            value,
+            rate_per_year,
+            acquisition_date: date(2024, 1, 31),
+            principal_payment: payment,
+            principal_frequency: frequency,
+            ..Loan::default()
+        }
+    }
+
+    #[test]
+    fn pays_off_on_calendar_dates() {
+        // Monthly payments keep to the end of the month rather than drifting by 30 days.
+        let monthly = loan(1200.0, 0.0, 100.0, ContributionFrequency::Monthly);
+        assert_eq!(monthly.payoff_date(), Some(date(2025, 1, 31)));
+        assert_eq!(monthly.value(date(2024, 2, 29)), 1100.0);
+        assert_eq!(monthly.value(date(2024, 2, 28)), 1200.0);
+
+        // 1000 grows to about 1100 in the first year, 500 is left after paying 600, and the
+        // second year's 550 is paid off in full.
+        let yearly = loan(1000.0, 10.0, 600.0, ContributionFrequency::Yearly);
+        assert_eq!(yearly.payoff_date(), Some(date(2026, 1, 31)));
+        assert!((yearly.principal_paid(date(2026, 1, 31)) - 1150.0).abs() < 1.0);
+
+        let never = loan(1000.0, 10.0, 50.0, ContributionFrequency::Yearly);
+        assert_eq!(never.payoff_date(), None);
+    }
+
+    #[test]
+    fn follows_rate_changes() {
+        let mut loan = loan(1000.0, 0.0, 0.0, ContributionFrequency::Yearly);
+        loan.rate_schedule = Some(RateSchedule {
+            changes: vec![RateChange {
+                date: date(2025, 1, 31),
+                rate_per_year: 10.0,
+            }],
+            renewal_months: 12,
+        });
+        assert_eq!(loan.value(date(2025, 1, 31)), 1000.0);
+        assert!((loan.value(date(2026, 1, 31)) - 1100.0).abs() < 0.01);
+        assert_eq!(loan.rate_at(date(2025, 1, 30)), 0.0);
+        assert_eq!(loan.rate_at(date(2025, 1, 31)), 10.0);
+    }
+
+    #[test]
+    fn charges_interest_across_a_payment() {
+        let loan = loan(1200.0, 12.0, 100.0, ContributionFrequency::Monthly);
+        let grow = |value: f32, days: f32| value * 1.12_f32.powf(days / 365.0);
+
+        // Interest on the full balance until the payment on February 29, then on what is left.
+        let before_payment = grow(1200.0, 29.0);
+        let after_payment = before_payment - 100.0;
+        let expected =
+            (before_payment - grow(1200.0, 14.0)) + (grow(after_payment, 14.0) - after_payment);
+
+        let interest = loan.interest_between(date(2024, 2, 14), date(2024, 3, 14));
+        assert!(
+            (interest - expected).abs() < 0.01,
+            "{interest} != {expected}"
+        );
+        assert_eq!(loan.principal_paid(date(2024, 3, 14)), 100.0);
+    }
+}
@@ -1,8 +1,16 @@
+pub mod bond;
 pub mod cash;
+pub mod credit_card;
+pub mod depreciating;
+pub mod equity_grant;
 pub mod loan;
 pub mod real_estate;
 pub mod tradable;
+pub use bond::Bond;
 pub use cash::Cash;
+pub use credit_card::CreditCard;
+pub use depreciating::Depreciating;
+pub use equity_grant::EquityGrant;
 pub use loan::Loan;
 pub use real_estate::RealEstate;
 pub use tradable::Tradable;
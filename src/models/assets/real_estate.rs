@@ -1,8 +1,22 @@
+use chrono::Months;
 pub use chrono::NaiveDate;
 use egui::Ui;
 use uuid::Uuid;
 
-use crate::{asset::AssetTrait, plot_utils::get_random_bytes_from_uuid};
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    i18n::tr,
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, date_edit, number_drag_value},
+    RateSchedule,
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SaleDetails {
+    pub date: NaiveDate,
+    pub transaction_cost_rate: f32, // broker fees etc. (% of the sale price)
+    pub capital_gains_tax_rate: f32, // tax on the gain (%)
+}
 
 #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct RealEstate {
@@ -13,12 +27,27 @@ pub struct RealEstate {
     pub acquisition_date: NaiveDate,
     pub should_delete: bool,
     pub color: egui::Color32,
+    #[serde(default)]
+    pub monthly_rent: f32,
+    #[serde(default)]
+    pub monthly_maintenance: f32,
+    #[serde(default)]
+    pub property_tax_rate: f32, // yearly tax (% of the property value)
+    #[serde(default)]
+    pub yearly_fees: f32, // insurance, association fees etc.
+    #[serde(default)]
+    pub sale_details: Option<SaleDetails>,
+    #[serde(default)]
+    pub payout_target: Option<Uuid>, // cash asset receiving net cash flows and sale proceeds, if any
+    #[serde(default)]
+    pub rate_schedule: Option<RateSchedule>, // appreciation that changes over time
+    #[serde(default)]
+    pub meta: AssetMeta,
 }
 impl Default for RealEstate {
     fn default() -> Self {
         let uuid = Uuid::new_v4();
-        // create red nuanced color from uuid
-        let color = egui::Color32::from_rgb(70, get_random_bytes_from_uuid(&uuid), 70);
+        let color = Self::color_for(&uuid);
         Self {
             uuid,
             name: "Real Estate".to_owned(),
@@ -27,32 +56,83 @@ impl Default for RealEstate {
             acquisition_date: chrono::Utc::now().date_naive(),
             should_delete: false,
             color,
+            monthly_rent: 0.0,
+            monthly_maintenance: 0.0,
+            property_tax_rate: 0.0,
+            yearly_fees: 0.0,
+            sale_details: None,
+            payout_target: None,
+            rate_schedule: None,
+            meta: AssetMeta::default(),
         }
     }
 }
 
-impl AssetTrait for RealEstate {
-    fn value(&self, date: NaiveDate) -> f32 {
+impl RealEstate {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        // create red nuanced color from uuid
+        egui::Color32::from_rgb(70, get_random_bytes_from_uuid(uuid), 70)
+    }
+
+    /// Market value of the property on the given date, ignoring any planned sale.
+    pub fn market_value(&self, date: NaiveDate) -> f32 {
         // If the provided date is before (or on) the acquisition date,
         // we return the initial value.
         if date <= self.acquisition_date {
             return self.value;
         }
 
-        // Calculate the number of days elapsed since the acquisition.
-        let duration = date.signed_duration_since(self.acquisition_date);
-        let days_elapsed = duration.num_days() as f32;
-
-        // Convert days elapsed into fractional years.
-        let years_elapsed = days_elapsed / 365.0; // You can adjust to 365.25 if needed.
-
-        // Compute the growth factor. Here, rate_per_year is assumed to be in percent.
-        let growth_multiplier = (1.0 + self.rate_per_year / 100.0).powf(years_elapsed);
+        // Compute the growth factor, following the rate schedule if there is one.
+        // Here, rate_per_year is assumed to be in percent.
+        let growth_multiplier = RateSchedule::optional_growth_multiplier(
+            &self.rate_schedule,
+            self.rate_per_year,
+            self.acquisition_date,
+            date,
+        );
 
         // The asset's value on the given date is the initial value multiplied by the growth factor.
         self.value * growth_multiplier
     }
 
+    /// Rent minus running costs for the month starting on the given date.
+    pub fn monthly_net_cash_flow(&self, date: NaiveDate) -> f32 {
+        let property_tax = self.market_value(date) * self.property_tax_rate / 100.0 / 12.0;
+        self.monthly_rent - self.monthly_maintenance - property_tax - self.yearly_fees / 12.0
+    }
+
+    /// What is left of the sale price after transaction costs and capital gains tax.
+    pub fn sale_proceeds(&self, sale: &SaleDetails) -> f32 {
+        let sale_price = self.market_value(sale.date);
+        let transaction_costs = sale_price * sale.transaction_cost_rate / 100.0;
+        let gain = (sale_price - transaction_costs - self.value).max(0.0);
+        sale_price - transaction_costs - gain * sale.capital_gains_tax_rate / 100.0
+    }
+
+    /// Value of the property while it is owned, and 0 once it is sold.
+    pub fn property_value(&self, date: NaiveDate) -> f32 {
+        if let Some(sale) = &self.sale_details {
+            if date >= sale.date {
+                return 0.0;
+            }
+        }
+        self.market_value(date)
+    }
+}
+
+impl AssetTrait for RealEstate {
+    fn value(&self, date: NaiveDate) -> f32 {
+        // Without a cash asset to pay into, net cash flows and sale proceeds stay with the
+        // property.
+        let kept: f32 = if self.payout_target.is_none() {
+            self.payouts(date).iter().map(|(_, amount)| amount).sum()
+        } else {
+            0.0
+        };
+        self.property_value(date) + kept
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
@@ -66,29 +146,119 @@ impl AssetTrait for RealEstate {
             });
 
             ui.horizontal(|ui| {
-                ui.label("Value: ");
+                ui.label(tr("Value: "));
+                modified |= ui
+                    .add(amount_drag_value(&mut self.value, &currency).speed(1000.0))
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Annual Rate (%): "));
                 modified |= ui
                     .add(
-                        egui::DragValue::new(&mut self.value)
-                            .speed(1000.0)
-                            .prefix(currency),
+                        number_drag_value(&mut self.rate_per_year)
+                            .speed(0.1)
+                            .range(RateSchedule::GROWTH_RATES),
                     )
                     .changed();
             });
 
+            // Variable rate schedule.
+            modified |= RateSchedule::optional_ui_edit(
+                ui,
+                ("real_estate_rate_schedule", self.uuid),
+                &mut self.rate_schedule,
+                self.rate_per_year,
+                self.acquisition_date,
+                RateSchedule::GROWTH_RATES,
+            );
+
+            // Running income and costs.
             ui.horizontal(|ui| {
-                ui.label("Annual Rate (%): ");
+                ui.label(tr("Monthly Rent: "));
                 modified |= ui
                     .add(
-                        egui::DragValue::new(&mut self.rate_per_year)
-                            .speed(0.1)
-                            .range(-20.0..=20.0),
+                        amount_drag_value(&mut self.monthly_rent, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
                     )
                     .changed();
             });
 
             ui.horizontal(|ui| {
-                ui.label("Acquisition Date: ");
+                ui.label(tr("Monthly Maintenance: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.monthly_maintenance, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Property Tax (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.property_tax_rate)
+                            .speed(0.01)
+                            .range(0.0..=10.0),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Yearly Fees: "));
+                modified |= ui
+                    .add(
+                        amount_drag_value(&mut self.yearly_fees, &currency)
+                            .speed(100.0)
+                            .range(0.0..=f32::MAX),
+                    )
+                    .changed();
+            });
+
+            // Planned sale.
+            let mut planned_sale = self.sale_details.is_some();
+            if ui.checkbox(&mut planned_sale, tr("Planned Sale")).changed() {
+                self.sale_details = planned_sale.then(|| SaleDetails {
+                    date: self.acquisition_date + Months::new(12 * 10),
+                    transaction_cost_rate: 2.0,
+                    capital_gains_tax_rate: 22.0,
+                });
+                modified = true;
+            }
+            if let Some(sale) = &mut self.sale_details {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Sale Date: "));
+                    modified |= date_edit(ui, &mut sale.date);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Transaction Costs (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut sale.transaction_cost_rate)
+                                .speed(0.1)
+                                .range(0.0..=20.0),
+                        )
+                        .changed();
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Capital Gains Tax (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut sale.capital_gains_tax_rate)
+                                .speed(0.1)
+                                .range(0.0..=100.0),
+                        )
+                        .changed();
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Acquisition Date: "));
                 // You might want to add a date picker here
                 // For now, we'll just show the date
                 ui.label(self.acquisition_date.to_string());
@@ -113,4 +283,47 @@ impl AssetTrait for RealEstate {
     fn is_growth(&self) -> bool {
         true
     }
+
+    fn payouts(&self, date: NaiveDate) -> Vec<(NaiveDate, f32)> {
+        let end_date = match &self.sale_details {
+            Some(sale) => date.min(sale.date),
+            None => date,
+        };
+
+        // Net cash flows are settled at the end of every month, counted from the acquisition
+        // date so that the day of the month does not drift after a short month.
+        let mut payouts = Vec::new();
+        let mut month_start = self.acquisition_date;
+        for months in 1.. {
+            let Some(month_end) = self
+                .acquisition_date
+                .checked_add_months(Months::new(months))
+            else {
+                break;
+            };
+            if month_end > end_date {
+                break;
+            }
+            let cash_flow = self.monthly_net_cash_flow(month_start);
+            if cash_flow != 0.0 {
+                payouts.push((month_end, cash_flow));
+            }
+            month_start = month_end;
+        }
+
+        if let Some(sale) = &self.sale_details {
+            if date >= sale.date {
+                payouts.push((sale.date, self.sale_proceeds(sale)));
+            }
+        }
+        payouts
+    }
+
+    fn payout_target(&self) -> Option<Uuid> {
+        self.payout_target
+    }
+
+    fn payout_target_mut(&mut self) -> Option<&mut Option<Uuid>> {
+        Some(&mut self.payout_target)
+    }
 }
@@ -2,7 +2,15 @@ pub use chrono::NaiveDate;
 use egui::Ui;
 use uuid::Uuid;
 
-use crate::{asset::AssetTrait, plot_utils::get_random_bytes_from_uuid};
+use crate::{
+    asset::{AssetMeta, AssetTrait},
+    format::format_amount_with_decimals,
+    i18n::tr,
+    ledger::{Ledger, LedgerEntryKind},
+    plot_utils::get_random_bytes_from_uuid,
+    widgets::{amount_drag_value, number_drag_value},
+    ContributionPlan, Holding, RateSchedule,
+};
 
 #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum ContributionFrequency {
@@ -11,6 +19,26 @@ pub enum ContributionFrequency {
     Yearly,
 }
 
+impl ContributionFrequency {
+    /// Length of one period in days.
+    pub fn interval_days(&self) -> f32 {
+        match self {
+            ContributionFrequency::Weekly => 7.0,
+            ContributionFrequency::Monthly => 30.0,
+            ContributionFrequency::Yearly => 365.0,
+        }
+    }
+
+    /// How many periods there are in a month on average.
+    pub fn per_month(&self) -> f32 {
+        match self {
+            ContributionFrequency::Weekly => 52.0 / 12.0,
+            ContributionFrequency::Monthly => 1.0,
+            ContributionFrequency::Yearly => 1.0 / 12.0,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct CashoutDetails {
     pub date: NaiveDate,
@@ -30,13 +58,51 @@ pub struct Tradable {
     pub should_delete: bool,
     pub color: egui::Color32,
     pub cashout_details: Option<CashoutDetails>,
+    #[serde(default)]
+    pub rate_schedule: Option<RateSchedule>, // expected return that changes over time
+    #[serde(default)]
+    pub contribution_plan: ContributionPlan,
+    #[serde(default)]
+    pub dividend_yield: f32, // annual dividend (% of value), on top of rate_per_year
+    #[serde(default = "default_dividend_frequency")]
+    pub dividend_frequency: ContributionFrequency,
+    #[serde(default)]
+    pub dividend_tax_rate: f32, // tax withheld on each dividend (%)
+    #[serde(default = "default_reinvest_dividends")]
+    pub reinvest_dividends: bool,
+    #[serde(default)]
+    pub payout_target: Option<Uuid>, // cash asset receiving dividends that are not reinvested
+    #[serde(default)]
+    pub expense_ratio: f32, // yearly fund fee (% of value)
+    #[serde(default)]
+    pub platform_fee: f32, // yearly platform or custody fee (% of value)
+    #[serde(default)]
+    pub holding: Option<Holding>, // concrete shares with a price history, replaces `value`
+    #[serde(default)]
+    pub ledger: Ledger, // recorded buys, sells, dividends and fees
+    #[serde(default)]
+    pub meta: AssetMeta,
+}
+
+/// Result of stepping a holding forward in time.
+struct Simulation {
+    value: f32,
+    payouts: Vec<(NaiveDate, f32)>,
+    fees: f32,
+}
+
+fn default_dividend_frequency() -> ContributionFrequency {
+    ContributionFrequency::Yearly
+}
+
+fn default_reinvest_dividends() -> bool {
+    true
 }
 
 impl Default for Tradable {
     fn default() -> Self {
         let uuid = Uuid::new_v4();
-        // create a red nuanced color from uuid
-        let color = egui::Color32::from_rgb(70, get_random_bytes_from_uuid(&uuid), 70);
+        let color = Self::color_for(&uuid);
         Self {
             uuid,
             name: "Stocks".to_owned(),
@@ -48,56 +114,193 @@ impl Default for Tradable {
             should_delete: false,
             color,
             cashout_details: None,
+            rate_schedule: None,
+            contribution_plan: ContributionPlan::default(),
+            dividend_yield: 0.0,
+            dividend_frequency: default_dividend_frequency(),
+            dividend_tax_rate: 0.0,
+            reinvest_dividends: default_reinvest_dividends(),
+            payout_target: None,
+            expense_ratio: 0.0,
+            platform_fee: 0.0,
+            holding: None,
+            ledger: Ledger::default(),
+            meta: AssetMeta::default(),
         }
     }
 }
 
-impl AssetTrait for Tradable {
-    fn value(&self, date: NaiveDate) -> f32 {
-        // If the target date is on or before the acquisition, return the initial value.
-        if date <= self.acquisition_date {
-            return self.value;
+impl Tradable {
+    /// The color of an asset of this type with the given uuid.
+    pub fn color_for(uuid: &Uuid) -> egui::Color32 {
+        // create a red nuanced color from uuid
+        egui::Color32::from_rgb(70, get_random_bytes_from_uuid(uuid), 70)
+    }
+
+    /// Whether dividends are paid out to a cash asset. Dividends set to be paid out without a
+    /// target are reinvested, so they are not lost.
+    pub fn pays_out_dividends(&self) -> bool {
+        !self.reinvest_dividends && self.payout_target.is_some()
+    }
+
+    /// Growth multiplier between two dates, following the rate schedule if there is one.
+    pub fn growth_multiplier(&self, start: NaiveDate, end: NaiveDate) -> f32 {
+        RateSchedule::optional_growth_multiplier(
+            &self.rate_schedule,
+            self.rate_per_year,
+            start,
+            end,
+        )
+    }
+
+    /// Value recorded up to `date`. Within the holding's price history the value follows the
+    /// real prices, with the ledger entries buying or selling shares. After it, it is the
+    /// latest holding value plus the ledger entries since. Without a price history the ledger
+    /// adds to the initial value.
+    pub fn recorded_value(&self, date: NaiveDate) -> f32 {
+        let Some((holding, latest)) = self
+            .holding
+            .as_ref()
+            .and_then(|holding| Some((holding, holding.latest_price()?)))
+        else {
+            return self.value + self.ledger.change_until(date);
+        };
+        if date <= latest.date {
+            return self.holding_value(holding, date).unwrap_or(self.value);
         }
+        self.holding_value(holding, latest.date)
+            .unwrap_or(self.value)
+            + self.ledger.change_between(latest.date, date)
+    }
 
-        let duration = date.signed_duration_since(self.acquisition_date);
-        let total_days = duration.num_days() as f32;
-        let years_elapsed = total_days / 365.0;
-        let rate = self.rate_per_year / 100.0;
+    /// Value of the holding on a date within its price history. Each ledger entry up to the
+    /// date buys or sells shares at the price on its own date.
+    fn holding_value(&self, holding: &Holding, date: NaiveDate) -> Option<f32> {
+        let price = holding.known_price(date)?;
+        let traded_shares: f32 = self
+            .ledger
+            .entries()
+            .iter()
+            .take_while(|entry| entry.date <= date)
+            .filter_map(|entry| {
+                let entry_price = holding
+                    .known_price(entry.date)
+                    .filter(|price| *price > 0.0)?;
+                Some(entry.signed_amount() / entry_price)
+            })
+            .sum();
+        Some((holding.shares + traded_shares) * price)
+    }
 
-        // Compound the initial investment.
-        let compounded_initial = self.value * (1.0 + rate).powf(years_elapsed);
+    /// Date of the last known price or ledger entry, after which the projection takes over.
+    pub fn history_end(&self) -> Option<NaiveDate> {
+        let latest_price = self
+            .holding
+            .as_ref()
+            .and_then(|holding| holding.latest_price())
+            .map(|latest| latest.date);
+        latest_price.max(self.ledger.last_date())
+    }
 
-        // Determine the interval (in days) based on the contribution frequency.
-        let interval_days = match self.contribution_frequency {
-            ContributionFrequency::Weekly => 7.0,
-            ContributionFrequency::Monthly => 30.0,
-            ContributionFrequency::Yearly => 365.0,
+    /// Value and date the projection starts from: the end of the recorded history if there
+    /// is one, otherwise the initial value at acquisition.
+    pub fn projection_start(&self) -> (f32, NaiveDate) {
+        match self.history_end() {
+            Some(end) => (self.recorded_value(end), end),
+            None => (self.value, self.acquisition_date),
+        }
+    }
+
+    /// Growth from `start` to `end` after fees, and the fees charged on `value` over that time.
+    fn grow_with_fees(&self, value: f32, start: NaiveDate, end: NaiveDate) -> (f32, f32) {
+        let gross_value = value * self.growth_multiplier(start, end);
+        let years = (end - start).num_days() as f32 / 365.0;
+        let fee_rate = (self.expense_ratio + self.platform_fee) / 100.0;
+        let net_value = gross_value * (1.0 - fee_rate).max(0.0).powf(years);
+        (net_value, gross_value - net_value)
+    }
+
+    /// Steps through contributions and dividends in date order up to `date`, compounding the
+    /// holding and charging fees in between.
+    fn simulate(&self, date: NaiveDate) -> Simulation {
+        let (start_value, start_date) = self.projection_start();
+        let mut simulation = Simulation {
+            value: start_value,
+            payouts: Vec::new(),
+            fees: 0.0,
+        };
+        // If the target date is on or before the start, return the initial value.
+        if date <= start_date {
+            return simulation;
+        }
+
+        let contribution_interval = self.contribution_frequency.interval_days();
+        let dividend_interval = self.dividend_frequency.interval_days();
+        let period_date = |interval: f32, index: i32| {
+            start_date + chrono::Duration::days((index as f32 * interval) as i64)
         };
 
-        // Determine how many contribution periods have passed.
-        let num_periods = (total_days / interval_days).floor() as i32;
-        let mut compounded_contributions = 0.0;
+        let mut last_date = start_date;
+        let mut contribution_index = 1;
+        let mut dividend_index = 1;
 
-        // For each contribution, compound it from its deposit date until the target date.
-        for i in 1..=num_periods {
-            // Compute the contribution date.
-            let contribution_date =
-                self.acquisition_date + chrono::Duration::days((i as f32 * interval_days) as i64);
-            if contribution_date > date {
+        loop {
+            let contribution_date = period_date(contribution_interval, contribution_index);
+            let dividend_date = if self.dividend_yield > 0.0 {
+                period_date(dividend_interval, dividend_index)
+            } else {
+                NaiveDate::MAX
+            };
+            let next_date = contribution_date.min(dividend_date);
+            if next_date > date {
                 break;
             }
-            let days_since_contribution = (date - contribution_date).num_days() as f32;
-            let years_since_contribution = days_since_contribution / 365.0;
-            compounded_contributions +=
-                self.contribution * (1.0 + rate).powf(years_since_contribution);
+
+            let (value, fees) = self.grow_with_fees(simulation.value, last_date, next_date);
+            simulation.value = value;
+            simulation.fees += fees;
+            last_date = next_date;
+
+            if dividend_date == next_date {
+                let dividend = simulation.value * self.dividend_yield / 100.0 * dividend_interval
+                    / 365.0
+                    * (1.0 - self.dividend_tax_rate / 100.0);
+                if self.pays_out_dividends() {
+                    simulation.payouts.push((dividend_date, dividend));
+                } else {
+                    simulation.value += dividend;
+                }
+                dividend_index += 1;
+            }
+            if contribution_date == next_date {
+                simulation.value += self.contribution_plan.amount(
+                    self.contribution,
+                    self.acquisition_date,
+                    contribution_date,
+                );
+                contribution_index += 1;
+            }
         }
 
+        let (value, fees) = self.grow_with_fees(simulation.value, last_date, date);
+        simulation.value = value;
+        simulation.fees += fees;
+        simulation
+    }
+}
+
+impl AssetTrait for Tradable {
+    fn value(&self, date: NaiveDate) -> f32 {
         if let Some(cashout) = &self.cashout_details {
             if date >= cashout.date {
                 return 0.0;
             }
         }
-        compounded_initial + compounded_contributions
+        // Within the recorded history the value follows the real prices and ledger.
+        if self.history_end().is_some_and(|end| date <= end) {
+            return self.recorded_value(date);
+        }
+        self.simulate(date).value
     }
 
     fn name(&self) -> String {
@@ -113,69 +316,169 @@ impl AssetTrait for Tradable {
                 modified |= ui.text_edit_singleline(&mut self.name).changed();
             });
 
-            // Initial Value.
-            ui.horizontal(|ui| {
-                ui.label("Value: ");
-                modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.value)
-                            .speed(1000.0)
-                            .prefix(currency.clone()),
-                    )
-                    .changed();
-            });
+            // Initial Value, or shares and prices for a concrete holding.
+            let mut is_holding = self.holding.is_some();
+            if ui.checkbox(&mut is_holding, tr("Holding")).changed() {
+                self.holding = is_holding.then(Holding::default);
+                modified = true;
+            }
+            match &mut self.holding {
+                Some(holding) => {
+                    modified |= holding_ui_edit(ui, holding, self.uuid, &currency);
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Value: "));
+                        modified |= ui
+                            .add(amount_drag_value(&mut self.value, &currency).speed(1000.0))
+                            .changed();
+                    });
+                }
+            }
 
             // Annual Growth Rate.
             ui.horizontal(|ui| {
-                ui.label("Annual Rate (%): ");
+                ui.label(tr("Annual Rate (%): "));
                 modified |= ui
                     .add(
-                        egui::DragValue::new(&mut self.rate_per_year)
+                        number_drag_value(&mut self.rate_per_year)
                             .speed(0.1)
-                            .range(-20.0..=20.0),
+                            .range(RateSchedule::GROWTH_RATES),
                     )
                     .changed();
             });
 
+            // Variable rate schedule.
+            modified |= RateSchedule::optional_ui_edit(
+                ui,
+                ("tradable_rate_schedule", self.uuid),
+                &mut self.rate_schedule,
+                self.rate_per_year,
+                self.acquisition_date,
+                RateSchedule::GROWTH_RATES,
+            );
+
             // Contribution Amount.
             ui.horizontal(|ui| {
-                ui.label("Contribution: ");
+                ui.label(tr("Contribution: "));
                 modified |= ui
-                    .add(
-                        egui::DragValue::new(&mut self.contribution)
-                            .speed(10.0)
-                            .prefix(currency),
-                    )
+                    .add(amount_drag_value(&mut self.contribution, &currency).speed(10.0))
                     .changed();
             });
 
             // Contribution Frequency.
             ui.horizontal(|ui| {
-                ui.label("Frequency: ");
+                ui.label(tr("Frequency: "));
                 egui::ComboBox::from_id_salt("tradable_frequency")
-                    .selected_text(format!("{:?}", self.contribution_frequency))
+                    .selected_text(tr(&format!("{:?}", self.contribution_frequency)))
                     .show_ui(ui, |ui| {
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Weekly,
-                            "Weekly",
+                            tr("Weekly"),
                         );
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Monthly,
-                            "Monthly",
+                            tr("Monthly"),
                         );
                         ui.selectable_value(
                             &mut self.contribution_frequency,
                             ContributionFrequency::Yearly,
-                            "Yearly",
+                            tr("Yearly"),
                         );
                     });
             });
 
+            // Escalation, end date and pauses.
+            ui.collapsing(tr("Contribution Plan"), |ui| {
+                modified |= self.contribution_plan.ui_edit(ui, self.acquisition_date);
+            });
+
+            // Fees.
+            ui.horizontal(|ui| {
+                ui.label(tr("Expense Ratio (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.expense_ratio)
+                            .speed(0.01)
+                            .range(0.0..=5.0),
+                    )
+                    .changed();
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("Platform Fee (%): "));
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut self.platform_fee)
+                            .speed(0.01)
+                            .range(0.0..=5.0),
+                    )
+                    .changed();
+            });
+
+            // Dividends.
+            ui.collapsing(tr("Dividends"), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Dividend Yield (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut self.dividend_yield)
+                                .speed(0.05)
+                                .range(0.0..=20.0),
+                        )
+                        .changed();
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Paid: "));
+                    egui::ComboBox::from_id_salt(("dividend_frequency", self.uuid))
+                        .selected_text(tr(&format!("{:?}", self.dividend_frequency)))
+                        .show_ui(ui, |ui| {
+                            modified |= ui
+                                .selectable_value(
+                                    &mut self.dividend_frequency,
+                                    ContributionFrequency::Weekly,
+                                    tr("Weekly"),
+                                )
+                                .changed();
+                            modified |= ui
+                                .selectable_value(
+                                    &mut self.dividend_frequency,
+                                    ContributionFrequency::Monthly,
+                                    tr("Monthly"),
+                                )
+                                .changed();
+                            modified |= ui
+                                .selectable_value(
+                                    &mut self.dividend_frequency,
+                                    ContributionFrequency::Yearly,
+                                    tr("Yearly"),
+                                )
+                                .changed();
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(tr("Dividend Tax (%): "));
+                    modified |= ui
+                        .add(
+                            number_drag_value(&mut self.dividend_tax_rate)
+                                .speed(0.1)
+                                .range(0.0..=100.0),
+                        )
+                        .changed();
+                });
+
+                modified |= ui
+                    .checkbox(&mut self.reinvest_dividends, tr("Reinvest Dividends"))
+                    .changed();
+            });
+
             // Acquisition Date (display only for now).
             ui.horizontal(|ui| {
-                ui.label("Acquisition Date: ");
+                ui.label(tr("Acquisition Date: "));
                 ui.label(self.acquisition_date.to_string());
             });
         });
@@ -198,4 +501,172 @@ impl AssetTrait for Tradable {
     fn is_growth(&self) -> bool {
         true
     }
+
+    fn history_start(&self) -> Option<NaiveDate> {
+        let first_price = self.holding.as_ref().and_then(Holding::first_date);
+        match (first_price, self.ledger.first_date()) {
+            (Some(price), Some(entry)) => Some(price.min(entry)),
+            (price, entry) => price.or(entry),
+        }
+    }
+
+    fn fees_paid(&self, date: NaiveDate) -> f32 {
+        let end_date = match &self.cashout_details {
+            Some(cashout) => date.min(cashout.date),
+            None => date,
+        };
+        self.simulate(end_date).fees + self.ledger.total(LedgerEntryKind::Fee, end_date)
+    }
+
+    fn payouts(&self, date: NaiveDate) -> Vec<(NaiveDate, f32)> {
+        if !self.pays_out_dividends() {
+            return Vec::new();
+        }
+        // Dividends stop when the holding is cashed out.
+        let end_date = match &self.cashout_details {
+            Some(cashout) => date.min(cashout.date),
+            None => date,
+        };
+        self.simulate(end_date).payouts
+    }
+
+    fn payout_target(&self) -> Option<Uuid> {
+        self.payout_target.filter(|_| !self.reinvest_dividends)
+    }
+
+    fn payout_target_mut(&mut self) -> Option<&mut Option<Uuid>> {
+        if self.reinvest_dividends {
+            return None;
+        }
+        Some(&mut self.payout_target)
+    }
+
+    fn ledger_mut(&mut self) -> Option<&mut Ledger> {
+        Some(&mut self.ledger)
+    }
+}
+
+/// Ticker, share count and price history import for a holding.
+fn holding_ui_edit(ui: &mut Ui, holding: &mut Holding, uuid: Uuid, currency: &str) -> bool {
+    let mut modified = false;
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Ticker: "));
+        modified |= ui.text_edit_singleline(&mut holding.ticker).changed();
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Shares: "));
+        modified |= ui
+            .add(
+                number_drag_value(&mut holding.shares)
+                    .speed(1.0)
+                    .range(0.0..=f32::MAX),
+            )
+            .changed();
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Latest Price: "));
+        match holding.latest_price() {
+            Some(latest) => ui.label(format!(
+                "{} ({})",
+                format_amount_with_decimals(latest.price, currency, 2),
+                latest.date
+            )),
+            None => ui.label(tr("No prices")),
+        };
+    });
+
+    // Price history import, kept in temporary memory until imported.
+    ui.collapsing(tr("Import Prices (date, price)"), |ui| {
+        let text_id = ui.id().with(("price_csv", uuid));
+        let status_id = ui.id().with(("price_csv_status", uuid));
+        let mut text: String = ui.data_mut(|data| data.get_temp(text_id).unwrap_or_default());
+        ui.add(
+            egui::TextEdit::multiline(&mut text)
+                .hint_text("2024-01-31,123.4")
+                .desired_rows(4),
+        );
+        ui.horizontal(|ui| {
+            if ui.button(tr("Import")).clicked() {
+                let status = match holding.import_prices_csv(&text) {
+                    Ok(imported) => {
+                        modified = true;
+                        text.clear();
+                        format!("{} {imported}", tr("Imported prices:"))
+                    }
+                    Err(error) => tr(&error).to_owned(),
+                };
+                ui.data_mut(|data| data.insert_temp(status_id, status));
+            }
+            if ui.button(tr("Clear History")).clicked() {
+                holding.price_history.clear();
+                modified = true;
+            }
+        });
+        if let Some(status) = ui.data(|data| data.get_temp::<String>(status_id)) {
+            ui.label(status);
+        }
+        ui.data_mut(|data| data.insert_temp(text_id, text));
+    });
+
+    modified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{holding::PricePoint, ledger::LedgerEntry};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn entry(date: NaiveDate, kind: LedgerEntryKind, amount: f32) -> LedgerEntry {
+        LedgerEntry {
+            date,
+            kind,
+            amount,
+            note: String::new(),
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn trades_shares_within_the_price_history() {
+        let prices = [
+            (date(2024, 1, 1), 10.0),
+            (date(2024, 2, 1), 20.0),
+            (date(2024, 3, 1), 25.0),
+        ];
+        let mut tradable = Tradable {
+            acquisition_date: date(2024, 1, 1),
+            holding: Some(Holding {
+                ticker: "ABC".to_owned(),
+                shares: 10.0,
+                price_history: prices
+                    .iter()
+                    .map(|&(date, price)| PricePoint { date, price })
+                    .collect(),
+            }),
+            ..Tradable::default()
+        };
+        // 10 shares bought at 20, then 4 sold at 25, and 50 sold after the prices end.
+        tradable
+            .ledger
+            .append(entry(date(2024, 2, 1), LedgerEntryKind::Buy, 200.0));
+        tradable
+            .ledger
+            .append(entry(date(2024, 3, 1), LedgerEntryKind::Sell, 100.0));
+        tradable
+            .ledger
+            .append(entry(date(2024, 3, 10), LedgerEntryKind::Sell, 50.0));
+
+        assert_eq!(tradable.value(date(2024, 1, 15)), 100.0);
+        assert_eq!(tradable.value(date(2024, 2, 1)), 400.0);
+        assert_eq!(tradable.value(date(2024, 2, 15)), 400.0);
+        assert_eq!(tradable.value(date(2024, 3, 1)), 400.0);
+        assert_eq!(tradable.value(date(2024, 3, 10)), 350.0);
+    }
 }
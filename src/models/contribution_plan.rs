@@ -0,0 +1,177 @@
+use chrono::{Duration, Months, NaiveDate};
+use egui::Ui;
+
+use crate::{
+    i18n::tr,
+    widgets::{date_edit, number_drag_value},
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PausePeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate, // inclusive
+}
+
+/// How a regular contribution changes over time.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ContributionPlan {
+    pub yearly_increase: f32, // contribution increase every full year since acquisition (%)
+    pub end_date: Option<NaiveDate>,
+    pub pauses: Vec<PausePeriod>,
+}
+
+impl ContributionPlan {
+    /// Moves the end date and pauses by `offset`, e.g. when the asset is moved to a later start.
+    pub fn shift_dates(&mut self, offset: Duration) {
+        if let Some(end_date) = &mut self.end_date {
+            *end_date += offset;
+        }
+        for pause in &mut self.pauses {
+            pause.start += offset;
+            pause.end += offset;
+        }
+    }
+
+    /// The contribution made on the given date, given the initial contribution and acquisition date.
+    pub fn amount(&self, contribution: f32, acquisition_date: NaiveDate, date: NaiveDate) -> f32 {
+        if self.is_stopped(date) {
+            return 0.0;
+        }
+        contribution * (1.0 + self.yearly_increase / 100.0).powi(full_years(acquisition_date, date))
+    }
+
+    /// Whether the contribution made on `date` differs from the initial contribution: it has
+    /// ended, is paused or has been increased.
+    pub fn changes_amount(&self, acquisition_date: NaiveDate, date: NaiveDate) -> bool {
+        self.is_stopped(date)
+            || (self.yearly_increase != 0.0 && full_years(acquisition_date, date) > 0)
+    }
+
+    /// Whether no contribution is made on `date` because the plan has ended or is paused.
+    fn is_stopped(&self, date: NaiveDate) -> bool {
+        self.end_date.is_some_and(|end_date| date > end_date)
+            || self
+                .pauses
+                .iter()
+                .any(|pause| pause.start <= date && date <= pause.end)
+    }
+
+    /// Edits escalation, end date and pause windows. Returns true if the plan was changed.
+    pub fn ui_edit(&mut self, ui: &mut Ui, acquisition_date: NaiveDate) -> bool {
+        let mut modified = false;
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Yearly Increase (%): "));
+            modified |= ui
+                .add(
+                    number_drag_value(&mut self.yearly_increase)
+                        .speed(0.1)
+                        .range(-20.0..=20.0),
+                )
+                .changed();
+        });
+
+        ui.horizontal(|ui| {
+            let mut has_end_date = self.end_date.is_some();
+            if ui.checkbox(&mut has_end_date, tr("End Date: ")).changed() {
+                self.end_date = has_end_date.then(|| acquisition_date + Months::new(12 * 10));
+                modified = true;
+            }
+            if let Some(end_date) = &mut self.end_date {
+                modified |= date_edit(ui, end_date);
+            }
+        });
+
+        let mut index_to_remove = None;
+        for (index, pause) in self.pauses.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(tr("Pause: "));
+                modified |= date_edit(ui, &mut pause.start);
+                ui.label(tr("to"));
+                modified |= date_edit(ui, &mut pause.end);
+                if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
+                    index_to_remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = index_to_remove {
+            self.pauses.remove(index);
+            modified = true;
+        }
+        if ui.button(tr("Add Pause")).clicked() {
+            let start = self
+                .pauses
+                .last()
+                .map_or(acquisition_date, |pause| pause.end)
+                + Months::new(12);
+            self.pauses.push(PausePeriod {
+                start,
+                end: start + Months::new(6),
+            });
+            modified = true;
+        }
+
+        modified
+    }
+}
+
+/// Full years from acquisition to `date`, counted as 365 days each.
+fn full_years(acquisition_date: NaiveDate, date: NaiveDate) -> i32 {
+    ((date - acquisition_date).num_days().max(0) / 365) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{asset::Asset, tradable::ContributionFrequency, Cash};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn contribution_at(asset: &Asset, date: NaiveDate) -> f32 {
+        let (amount, frequency) = asset.contribution_at(date).unwrap();
+        assert_eq!(frequency, ContributionFrequency::Monthly);
+        amount
+    }
+
+    #[test]
+    fn follows_each_change_of_the_plan() {
+        let plan = ContributionPlan {
+            yearly_increase: 10.0,
+            end_date: Some(date(2026, 1, 1)),
+            pauses: vec![PausePeriod {
+                start: date(2024, 6, 1),
+                end: date(2024, 8, 31),
+            }],
+        };
+        let asset = Asset::Cash(Cash {
+            contribution: 1000.0,
+            contribution_frequency: ContributionFrequency::Monthly,
+            acquisition_date: date(2023, 1, 1),
+            contribution_plan: plan.clone(),
+            ..Cash::default()
+        });
+        let acquisition_date = date(2023, 1, 1);
+
+        // The first year is contributed as entered.
+        assert_eq!(contribution_at(&asset, date(2023, 12, 31)), 1000.0);
+        assert!(!plan.changes_amount(acquisition_date, date(2023, 12, 31)));
+
+        // Increased after every full year.
+        assert_eq!(contribution_at(&asset, date(2024, 1, 1)), 1100.0);
+        assert!(plan.changes_amount(acquisition_date, date(2024, 1, 1)));
+        assert!((contribution_at(&asset, date(2025, 1, 1)) - 1210.0).abs() < 1e-3);
+
+        // Nothing during a pause, both ends included.
+        assert_eq!(contribution_at(&asset, date(2024, 5, 31)), 1100.0);
+        assert_eq!(contribution_at(&asset, date(2024, 6, 1)), 0.0);
+        assert_eq!(contribution_at(&asset, date(2024, 8, 31)), 0.0);
+        assert_eq!(contribution_at(&asset, date(2024, 9, 1)), 1100.0);
+
+        // Nothing after the end date.
+        assert!((contribution_at(&asset, date(2026, 1, 1)) - 1331.0).abs() < 1e-3);
+        assert_eq!(contribution_at(&asset, date(2026, 1, 2)), 0.0);
+        assert!(plan.changes_amount(acquisition_date, date(2026, 1, 2)));
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::NaiveDate;
+
+use super::{
+    holding::PricePoint,
+    snapshot::{merge_snapshots, Snapshot},
+    Asset, Tradable,
+};
+
+/// Date formats accepted in imported files, tried in order.
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y", "%Y%m%d"];
+
+/// Splits CSV text into rows of trimmed fields. Both `,` and `;` separated files are
+/// accepted, the separator being guessed from the first line. Double quotes around
+/// fields are removed, separators inside quotes are kept and doubled quotes inside quotes
+/// are read as one.
+pub fn parse_rows(text: &str) -> Vec<Vec<String>> {
+    let separator = guess_separator(text);
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| split_line(line, separator))
+        .collect()
+}
+
+fn guess_separator(text: &str) -> char {
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.matches(';').count() > first_line.matches(',').count() {
+        ';'
+    } else {
+        ','
+    }
+}
+
+fn split_line(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if in_quotes && characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                fields.push(field.trim().to_owned());
+                field.clear();
+            }
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_owned());
+    fields
+}
+
+pub fn parse_date(field: &str) -> Option<NaiveDate> {
+    let field = field.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(field, format).ok())
+}
+
+/// Parses amounts such as `1234.5`, `1 234,50`, `-12,5` or `1,234.50`.
+pub fn parse_amount(field: &str) -> Option<f32> {
+    let cleaned: String = field
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\u{a0}')
+        .collect();
+    // With both separators present the last one is the decimal separator, and a comma alone is
+    // the decimal separator as in Swedish bank exports.
+    let normalized = match (cleaned.rfind(','), cleaned.rfind('.')) {
+        (Some(comma), Some(dot)) if comma > dot => cleaned.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => cleaned.replace(',', ""),
+        (Some(_), None) => cleaned.replace(',', "."),
+        _ => cleaned,
+    };
+    normalized.parse().ok()
+}
+
+/// What a CSV column holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum CsvColumn {
+    Ignore,
+    Date,
+    Amount,
+    Balance,
+    Ticker,
+    Price,
+}
+
+impl CsvColumn {
+    pub const ALL: [CsvColumn; 6] = [
+        CsvColumn::Ignore,
+        CsvColumn::Date,
+        CsvColumn::Amount,
+        CsvColumn::Balance,
+        CsvColumn::Ticker,
+        CsvColumn::Price,
+    ];
+
+    /// Guesses the column type from a header name, in English or Swedish.
+    pub fn guess(header: &str) -> Self {
+        let header = header.to_lowercase();
+        let contains_any = |names: &[&str]| names.iter().any(|name| header.contains(name));
+        if contains_any(&["date", "datum"]) {
+            CsvColumn::Date
+        } else if contains_any(&["balance", "saldo", "behållning"]) {
+            CsvColumn::Balance
+        } else if contains_any(&["amount", "belopp"]) {
+            CsvColumn::Amount
+        } else if contains_any(&["ticker", "symbol", "isin", "värdepapper"]) {
+            CsvColumn::Ticker
+        } else if contains_any(&["price", "close", "kurs"]) {
+            CsvColumn::Price
+        } else {
+            CsvColumn::Ignore
+        }
+    }
+}
+
+/// One parsed data row. Only rows with a valid date become records.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvRecord {
+    pub date: NaiveDate,
+    pub amount: Option<f32>,
+    pub balance: Option<f32>,
+    pub ticker: Option<String>,
+    pub price: Option<f32>,
+}
+
+/// CSV rows together with the meaning of each column.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CsvImport {
+    pub rows: Vec<Vec<String>>,
+    pub columns: Vec<CsvColumn>,
+    pub has_header: bool,
+}
+
+impl CsvImport {
+    /// Parses the text and guesses whether there is a header and what each column holds.
+    pub fn new(text: &str) -> Self {
+        let rows = parse_rows(text);
+        let first_row = rows.first().cloned().unwrap_or_default();
+        let has_header = !first_row.iter().any(|field| parse_date(field).is_some());
+        let columns = if has_header {
+            first_row
+                .iter()
+                .map(|header| CsvColumn::guess(header))
+                .collect()
+        } else {
+            first_row
+                .iter()
+                .map(|field| match parse_date(field) {
+                    Some(_) => CsvColumn::Date,
+                    None => CsvColumn::Ignore,
+                })
+                .collect()
+        };
+        Self {
+            rows,
+            columns,
+            has_header,
+        }
+    }
+
+    /// Parses the text with a fixed column mapping, skipping rows without a valid date.
+    pub fn with_columns(text: &str, columns: Vec<CsvColumn>) -> Self {
+        Self {
+            rows: parse_rows(text),
+            columns,
+            has_header: false,
+        }
+    }
+
+    pub fn header(&self) -> Option<&Vec<String>> {
+        self.rows.first().filter(|_| self.has_header)
+    }
+
+    pub fn data_rows(&self) -> &[Vec<String>] {
+        if self.has_header && !self.rows.is_empty() {
+            &self.rows[1..]
+        } else {
+            &self.rows
+        }
+    }
+
+    pub fn has_column(&self, column: CsvColumn) -> bool {
+        self.columns.contains(&column)
+    }
+
+    /// Rows with a valid date, oldest first. Files listing the newest row first are reversed
+    /// so that rows on the same date keep the order they happened in.
+    pub fn records(&self) -> Vec<CsvRecord> {
+        let field = |row: &Vec<String>, column: CsvColumn| {
+            self.columns
+                .iter()
+                .position(|mapped| *mapped == column)
+                .and_then(|index| row.get(index))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        let mut records: Vec<CsvRecord> = self
+            .data_rows()
+            .iter()
+            .filter_map(|row| {
+                Some(CsvRecord {
+                    date: parse_date(&field(row, CsvColumn::Date)?)?,
+                    amount: field(row, CsvColumn::Amount).and_then(|value| parse_amount(&value)),
+                    balance: field(row, CsvColumn::Balance).and_then(|value| parse_amount(&value)),
+                    ticker: field(row, CsvColumn::Ticker),
+                    price: field(row, CsvColumn::Price).and_then(|value| parse_amount(&value)),
+                })
+            })
+            .collect();
+        if records.first().map(|record| record.date) > records.last().map(|record| record.date) {
+            records.reverse();
+        }
+        records.sort_by_key(|record| record.date);
+        records
+    }
+
+    /// End-of-day balances. Uses the balance column if mapped, otherwise a running total of
+    /// the amounts starting from `opening_balance`.
+    pub fn balance_snapshots(&self, opening_balance: f32) -> Vec<Snapshot> {
+        let use_balance = self.has_column(CsvColumn::Balance);
+        let mut running_total = opening_balance;
+        let mut snapshots: Vec<Snapshot> = Vec::new();
+        for record in self.records() {
+            let value = if use_balance {
+                match record.balance {
+                    Some(balance) => balance,
+                    None => continue,
+                }
+            } else {
+                running_total += record.amount.unwrap_or(0.0);
+                running_total
+            };
+            match snapshots.last_mut() {
+                Some(last) if last.date == record.date => last.value = value,
+                _ => snapshots.push(Snapshot {
+                    date: record.date,
+                    value,
+                }),
+            }
+        }
+        snapshots
+    }
+
+    /// Distinct tickers in the ticker column.
+    pub fn tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self
+            .records()
+            .into_iter()
+            .filter_map(|record| record.ticker)
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        tickers
+    }
+
+    /// Prices from the price column, optionally only for one ticker.
+    pub fn prices(&self, ticker: Option<&str>) -> Vec<PricePoint> {
+        self.records()
+            .into_iter()
+            .filter(|record| ticker.is_none() || record.ticker.as_deref() == ticker)
+            .filter_map(|record| {
+                Some(PricePoint {
+                    date: record.date,
+                    price: record.price?,
+                })
+            })
+            .collect()
+    }
+
+    /// Imports balances into a cash asset or prices into a tradable holding. A file with
+    /// several tickers needs `ticker` to pick one. Returns the number of imported rows.
+    pub fn apply_to_asset(
+        &self,
+        asset: &mut Asset,
+        ticker: Option<&str>,
+        opening_balance: f32,
+    ) -> Result<usize, String> {
+        if !self.has_column(CsvColumn::Date) {
+            return Err("No column is mapped to Date".to_owned());
+        }
+        let imported = match asset {
+            Asset::Cash(cash) => {
+                if !self.has_column(CsvColumn::Balance) && !self.has_column(CsvColumn::Amount) {
+                    return Err("Map a Balance or Amount column to import balances".to_owned());
+                }
+                merge_snapshots(
+                    &mut cash.balance_history,
+                    self.balance_snapshots(opening_balance),
+                )
+            }
+            Asset::Tradable(Tradable {
+                holding: Some(holding),
+                ..
+            }) => {
+                if !self.has_column(CsvColumn::Price) {
+                    return Err("Map a Price column to import prices".to_owned());
+                }
+                if ticker.is_none() && self.tickers().len() > 1 {
+                    return Err("Select which ticker to import".to_owned());
+                }
+                let prices = self.prices(ticker);
+                let imported = prices.len();
+                holding.merge_prices(prices);
+                imported
+            }
+            _ => return Err("Only cash assets and holdings can be imported into".to_owned()),
+        };
+        if imported == 0 {
+            return Err("No rows with a valid date and value were found".to_owned());
+        }
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Cash, Holding};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_amounts() {
+        assert_eq!(parse_amount("1234.5"), Some(1234.5));
+        assert_eq!(parse_amount("1 234,50"), Some(1234.5));
+        assert_eq!(parse_amount("-12,5"), Some(-12.5));
+        assert_eq!(parse_amount("1,234.50"), Some(1234.5));
+        assert_eq!(parse_amount("1.234,50"), Some(1234.5));
+        assert_eq!(parse_amount("12,345"), Some(12.345));
+        assert_eq!(parse_amount("0,125"), Some(0.125));
+        assert_eq!(parse_amount("12,3456"), Some(12.3456));
+        assert_eq!(parse_amount("abc"), None);
+    }
+
+    #[test]
+    fn imports_balances_from_mapped_columns() {
+        // Newest row first, semicolon separated, with a Swedish header.
+        let import = CsvImport::new(
+            "Datum;Text;Belopp\n2024-01-03;Rent;-500,00\n2024-01-02;Salary;2 000,00\n2024-01-02;Fee;-10\n",
+        );
+        assert!(import.has_header);
+        assert_eq!(
+            import.columns,
+            vec![CsvColumn::Date, CsvColumn::Ignore, CsvColumn::Amount]
+        );
+
+        let mut asset = Asset::Cash(Cash::default());
+        assert_eq!(import.apply_to_asset(&mut asset, None, 100.0), Ok(2));
+        let Asset::Cash(cash) = asset else {
+            unreachable!()
+        };
+        assert_eq!(
+            cash.balance_history,
+            vec![
+                Snapshot {
+                    date: date(2024, 1, 2),
+                    value: 2090.0,
+                },
+                Snapshot {
+                    date: date(2024, 1, 3),
+                    value: 1590.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_prices_for_one_ticker() {
+        let mut import =
+            CsvImport::new("2024-01-31,ABC,10.5\n2024-01-31,XYZ,99\n2024-02-29,ABC,11\n");
+        assert!(!import.has_header);
+        import.columns = vec![CsvColumn::Date, CsvColumn::Ticker, CsvColumn::Price];
+        assert_eq!(import.tickers(), vec!["ABC".to_owned(), "XYZ".to_owned()]);
+
+        let mut asset = Asset::Tradable(Tradable {
+            holding: Some(Holding::default()),
+            ..Tradable::default()
+        });
+        // Mixing the tickers into one price history is refused.
+        assert!(import.apply_to_asset(&mut asset, None, 0.0).is_err());
+        assert_eq!(import.apply_to_asset(&mut asset, Some("ABC"), 0.0), Ok(2));
+        let Asset::Tradable(Tradable {
+            holding: Some(holding),
+            ..
+        }) = asset
+        else {
+            unreachable!()
+        };
+        assert_eq!(holding.price_at(date(2024, 2, 15)), Some(10.5));
+        assert_eq!(holding.price_at(date(2024, 3, 1)), Some(11.0));
+
+        // Cash assets need a balance or amount column.
+        let mut cash = Asset::Cash(Cash::default());
+        assert!(import.apply_to_asset(&mut cash, None, 0.0).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{Months, NaiveDate};
+use egui::Ui;
+
+use crate::{
+    i18n::tr,
+    widgets::{amount_drag_value, date_edit},
+};
+
+/// A net worth the portfolio should reach by a given date.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Goal {
+    pub name: String,
+    pub target_value: f32,
+    pub target_date: NaiveDate,
+}
+
+impl Default for Goal {
+    fn default() -> Self {
+        Self {
+            name: "New Goal".to_owned(),
+            target_value: 1_000_000.0,
+            target_date: chrono::Utc::now().date_naive() + Months::new(12 * 10),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoalStatus {
+    Reached,
+    OnTrack, // the projection reaches the target by the target date
+    Behind,
+}
+
+impl GoalStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalStatus::Reached => "Reached",
+            GoalStatus::OnTrack => "On track",
+            GoalStatus::Behind => "Behind",
+        }
+    }
+}
+
+impl Goal {
+    /// Status on `date`, given the net worth on that date and the projected net worth on the
+    /// target date.
+    pub fn status(&self, value: f32, projected_value: f32) -> GoalStatus {
+        if value >= self.target_value {
+            GoalStatus::Reached
+        } else if projected_value >= self.target_value {
+            GoalStatus::OnTrack
+        } else {
+            GoalStatus::Behind
+        }
+    }
+
+    /// Edits the name, target and date. Returns true if the goal was changed.
+    pub fn ui_edit(&mut self, ui: &mut Ui, currency: &str) -> bool {
+        let mut modified = false;
+
+        ui.horizontal(|ui| {
+            modified |= ui.text_edit_singleline(&mut self.name).changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Target: "));
+            modified |= ui
+                .add(amount_drag_value(&mut self.target_value, currency).speed(10000.0))
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("By: "));
+            modified |= date_edit(ui, &mut self.target_date);
+        });
+
+        modified
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+
+use super::csv::{CsvColumn, CsvImport};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct PricePoint {
+    pub date: NaiveDate,
+    pub price: f32,
+}
+
+/// A concrete position: a number of shares of a ticker, with its known price history.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Holding {
+    pub ticker: String,
+    pub shares: f32, // held before any of the asset's ledger entries
+    pub price_history: Vec<PricePoint>, // sorted by date
+}
+
+impl Holding {
+    /// The last known price on or before the given date.
+    pub fn price_at(&self, date: NaiveDate) -> Option<f32> {
+        self.price_history
+            .iter()
+            .take_while(|point| point.date <= date)
+            .last()
+            .map(|point| point.price)
+    }
+
+    /// The last known price on or before the given date. Before the price history starts the
+    /// first known price is used, so the holding does not drop to zero there.
+    pub fn known_price(&self, date: NaiveDate) -> Option<f32> {
+        self.price_at(date)
+            .or_else(|| self.price_history.first().map(|point| point.price))
+    }
+
+    /// Value of the shares on the given date.
+    pub fn value(&self, date: NaiveDate) -> Option<f32> {
+        Some(self.shares * self.known_price(date)?)
+    }
+
+    pub fn latest_price(&self) -> Option<&PricePoint> {
+        self.price_history.last()
+    }
+
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        self.price_history.first().map(|point| point.date)
+    }
+
+    /// Adds price points, replacing any existing price on the same date.
+    pub fn merge_prices(&mut self, prices: impl IntoIterator<Item = PricePoint>) {
+        for point in prices {
+            match self
+                .price_history
+                .binary_search_by_key(&point.date, |existing| existing.date)
+            {
+                Ok(index) => self.price_history[index] = point,
+                Err(index) => self.price_history.insert(index, point),
+            }
+        }
+    }
+
+    /// Imports a CSV with a date in the first column and a price in the second. Rows that
+    /// cannot be parsed, such as a header, are skipped. Returns the number of imported prices.
+    pub fn import_prices_csv(&mut self, text: &str) -> Result<usize, String> {
+        let prices =
+            CsvImport::with_columns(text, vec![CsvColumn::Date, CsvColumn::Price]).prices(None);
+        if prices.is_empty() {
+            return Err("No rows with a date and a price were found".to_owned());
+        }
+        let imported = prices.len();
+        self.merge_prices(prices);
+        Ok(imported)
+    }
+}
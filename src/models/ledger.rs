@@ -0,0 +1,223 @@
+use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    format::format_amount_with_decimals,
+    i18n::tr,
+    statement::Transaction,
+    widgets::{amount_drag_value, date_edit},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum LedgerEntryKind {
+    Deposit,
+    Withdrawal,
+    Fee,
+    Dividend,
+    Buy,  // securities bought into the asset
+    Sell, // securities sold out of the asset
+}
+
+impl LedgerEntryKind {
+    pub const ALL: [LedgerEntryKind; 6] = [
+        LedgerEntryKind::Deposit,
+        LedgerEntryKind::Withdrawal,
+        LedgerEntryKind::Fee,
+        LedgerEntryKind::Dividend,
+        LedgerEntryKind::Buy,
+        LedgerEntryKind::Sell,
+    ];
+    /// Kinds for a cash account, which holds no securities to buy or sell.
+    pub const CASH: [LedgerEntryKind; 4] = [
+        LedgerEntryKind::Deposit,
+        LedgerEntryKind::Withdrawal,
+        LedgerEntryKind::Fee,
+        LedgerEntryKind::Dividend,
+    ];
+
+    /// Whether entries of this kind add to (1.0) or subtract from (-1.0) the asset's value.
+    pub fn sign(&self) -> f32 {
+        match self {
+            LedgerEntryKind::Deposit | LedgerEntryKind::Dividend | LedgerEntryKind::Buy => 1.0,
+            LedgerEntryKind::Withdrawal | LedgerEntryKind::Fee | LedgerEntryKind::Sell => -1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LedgerEntry {
+    pub date: NaiveDate,
+    pub kind: LedgerEntryKind,
+    pub amount: f32, // always positive, the kind gives the direction
+    pub note: String,
+    #[serde(default)]
+    pub reference: Option<String>, // bank reference of imported entries, used to skip duplicates
+}
+
+impl LedgerEntry {
+    /// A deposit or withdrawal for an imported bank transaction, referencing it by its id.
+    pub fn from_transaction(transaction: &Transaction) -> Self {
+        Self {
+            date: transaction.date,
+            kind: if transaction.amount >= 0.0 {
+                LedgerEntryKind::Deposit
+            } else {
+                LedgerEntryKind::Withdrawal
+            },
+            amount: transaction.amount.abs(),
+            note: transaction.description.clone(),
+            reference: Some(transaction.id.clone()),
+        }
+    }
+
+    pub fn signed_amount(&self) -> f32 {
+        self.kind.sign() * self.amount
+    }
+}
+
+/// Append-only record of what actually happened to an asset. Entries can be added but never
+/// changed or removed, so the recorded history stays trustworthy.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>, // sorted by date, in the order they were added within a date
+}
+
+impl Ledger {
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an entry in date order. Entries with a reference that is already in the ledger
+    /// are skipped. Returns true if the entry was added.
+    pub fn append(&mut self, entry: LedgerEntry) -> bool {
+        if entry.reference.is_some()
+            && self
+                .entries
+                .iter()
+                .any(|existing| existing.reference == entry.reference)
+        {
+            return false;
+        }
+        let index = self
+            .entries
+            .partition_point(|existing| existing.date <= entry.date);
+        self.entries.insert(index, entry);
+        true
+    }
+
+    pub fn first_date(&self) -> Option<NaiveDate> {
+        self.entries.first().map(|entry| entry.date)
+    }
+
+    pub fn last_date(&self) -> Option<NaiveDate> {
+        self.entries.last().map(|entry| entry.date)
+    }
+
+    /// Net change in value from entries after `start` up to and including `end`.
+    pub fn change_between(&self, start: NaiveDate, end: NaiveDate) -> f32 {
+        self.entries
+            .iter()
+            .filter(|entry| start < entry.date && entry.date <= end)
+            .map(LedgerEntry::signed_amount)
+            .sum()
+    }
+
+    /// Net change in value from all entries up to and including `date`.
+    pub fn change_until(&self, date: NaiveDate) -> f32 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.date <= date)
+            .map(LedgerEntry::signed_amount)
+            .sum()
+    }
+
+    /// Total amount of entries of the given kind up to and including `date`.
+    pub fn total(&self, kind: LedgerEntryKind, date: NaiveDate) -> f32 {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind && entry.date <= date)
+            .map(|entry| entry.amount)
+            .sum()
+    }
+
+    /// Lists the entries and lets the user append a new one of the given kinds. Returns true if
+    /// an entry was added.
+    pub fn ui_edit(
+        &mut self,
+        ui: &mut Ui,
+        uuid: Uuid,
+        currency: &str,
+        kinds: &[LedgerEntryKind],
+    ) -> bool {
+        let mut modified = false;
+
+        if !self.entries.is_empty() {
+            egui::ScrollArea::vertical()
+                .id_salt(("ledger_entries", uuid))
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new(("ledger_grid", uuid))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for entry in self.entries.iter().rev() {
+                                ui.label(entry.date.to_string());
+                                ui.label(tr(&format!("{:?}", entry.kind)));
+                                ui.label(format_amount_with_decimals(
+                                    entry.signed_amount(),
+                                    currency,
+                                    2,
+                                ));
+                                ui.label(&entry.note);
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+
+        // The entry being written, kept in temporary memory until it is added.
+        let draft_id = ui.id().with(("ledger_draft", uuid));
+        let mut draft: LedgerEntry = ui.data_mut(|data| {
+            data.get_temp(draft_id).unwrap_or_else(|| LedgerEntry {
+                date: chrono::Utc::now().date_naive(),
+                kind: LedgerEntryKind::Deposit,
+                amount: 0.0,
+                note: String::new(),
+                reference: None,
+            })
+        });
+        ui.horizontal(|ui| {
+            date_edit(ui, &mut draft.date);
+            egui::ComboBox::from_id_salt(("ledger_kind", uuid))
+                .selected_text(tr(&format!("{:?}", draft.kind)))
+                .show_ui(ui, |ui| {
+                    for &kind in kinds {
+                        ui.selectable_value(&mut draft.kind, kind, tr(&format!("{:?}", kind)));
+                    }
+                });
+            ui.add(
+                amount_drag_value(&mut draft.amount, currency)
+                    .speed(10.0)
+                    .range(0.0..=f32::MAX),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut draft.note).hint_text(tr("Note")));
+            if ui
+                .add_enabled(draft.amount > 0.0, egui::Button::new(tr("Add Entry")))
+                .clicked()
+            {
+                modified |= self.append(draft.clone());
+                draft.amount = 0.0;
+                draft.note.clear();
+            }
+        });
+        ui.data_mut(|data| data.insert_temp(draft_id, draft));
+
+        modified
+    }
+}
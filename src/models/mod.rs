@@ -1,5 +1,19 @@
 pub mod asset;
 pub mod assets;
+pub mod contribution_plan;
+pub mod csv;
+pub mod goal;
+pub mod holding;
+pub mod ledger;
+pub mod rate_schedule;
+pub mod snapshot;
+pub mod statement;
 
 pub use asset::Asset;
 pub use assets::*;
+pub use contribution_plan::ContributionPlan;
+pub use goal::Goal;
+pub use holding::Holding;
+pub use ledger::Ledger;
+pub use rate_schedule::RateSchedule;
+pub use snapshot::Snapshot;
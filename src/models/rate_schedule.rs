@@ -0,0 +1,262 @@
+use std::ops::RangeInclusive;
+
+use chrono::{Duration, Months, NaiveDate};
+use egui::Ui;
+
+use crate::{
+    i18n::tr,
+    widgets::{date_edit, number_drag_value},
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RateChange {
+    pub date: NaiveDate,
+    pub rate_per_year: f32, // annual rate (%) in effect from `date`
+}
+
+/// A piecewise constant annual rate. Before the first change the asset's own rate applies.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RateSchedule {
+    pub changes: Vec<RateChange>,
+    pub renewal_months: u32, // spacing of newly added changes, e.g. 3 for a floating rate
+}
+
+impl Default for RateSchedule {
+    fn default() -> Self {
+        Self {
+            changes: Vec::new(),
+            renewal_months: 12,
+        }
+    }
+}
+
+impl RateSchedule {
+    /// Rates offered by the editor for assets that grow or lose value.
+    pub const GROWTH_RATES: RangeInclusive<f32> = -20.0..=20.0;
+    /// Rates offered by the editor for loan interest.
+    pub const LOAN_RATES: RangeInclusive<f32> = 0.0..=30.0;
+    /// Rates offered by the editor for interest or coupons earned.
+    pub const SAVINGS_RATES: RangeInclusive<f32> = 0.0..=20.0;
+    /// Rates offered by the editor for yearly depreciation.
+    pub const DEPRECIATION_RATES: RangeInclusive<f32> = 0.0..=100.0;
+
+    /// Moves every change by `offset`, e.g. when the asset is moved to a later start.
+    pub fn shift_dates(&mut self, offset: Duration) {
+        for change in &mut self.changes {
+            change.date += offset;
+        }
+    }
+
+    /// Whether a change has taken effect on or before the given date.
+    pub fn changes_rate_by(&self, date: NaiveDate) -> bool {
+        self.changes.iter().any(|change| change.date <= date)
+    }
+
+    /// The annual rate (%) in effect on the given date.
+    pub fn rate_at(&self, base_rate: f32, date: NaiveDate) -> f32 {
+        self.changes
+            .iter()
+            .filter(|change| change.date <= date)
+            .max_by_key(|change| change.date)
+            .map_or(base_rate, |change| change.rate_per_year)
+    }
+
+    /// Growth multiplier from `start` to `end`, compounding each rate over the days it is in effect.
+    pub fn growth_multiplier(&self, base_rate: f32, start: NaiveDate, end: NaiveDate) -> f32 {
+        if end <= start {
+            return 1.0;
+        }
+        let mut boundaries: Vec<NaiveDate> = self
+            .changes
+            .iter()
+            .map(|change| change.date)
+            .filter(|date| *date > start && *date < end)
+            .collect();
+        boundaries.sort();
+        boundaries.push(end);
+
+        let mut multiplier = 1.0;
+        let mut period_start = start;
+        for period_end in boundaries {
+            let rate = self.rate_at(base_rate, period_start) / 100.0;
+            let years = (period_end - period_start).num_days() as f32 / 365.0;
+            multiplier *= (1.0 + rate).powf(years);
+            period_start = period_end;
+        }
+        multiplier
+    }
+
+    /// The annual rate (%) in effect on the given date for an asset with an optional schedule.
+    pub fn optional_rate_at(
+        schedule: &Option<RateSchedule>,
+        base_rate: f32,
+        date: NaiveDate,
+    ) -> f32 {
+        match schedule {
+            Some(schedule) => schedule.rate_at(base_rate, date),
+            None => base_rate,
+        }
+    }
+
+    /// Growth multiplier from `start` to `end` for an asset with an optional schedule.
+    pub fn optional_growth_multiplier(
+        schedule: &Option<RateSchedule>,
+        base_rate: f32,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> f32 {
+        match schedule {
+            Some(schedule) => schedule.growth_multiplier(base_rate, start, end),
+            None if end <= start => 1.0,
+            None => (1.0 + base_rate / 100.0).powf((end - start).num_days() as f32 / 365.0),
+        }
+    }
+
+    /// Checkbox that turns an optional schedule on and off, followed by its editor.
+    /// Returns true if the schedule was changed.
+    pub fn optional_ui_edit(
+        ui: &mut Ui,
+        id_salt: impl std::hash::Hash,
+        schedule: &mut Option<RateSchedule>,
+        base_rate: f32,
+        start_date: NaiveDate,
+        rates: RangeInclusive<f32>,
+    ) -> bool {
+        let mut modified = false;
+        let mut variable_rate = schedule.is_some();
+        if ui
+            .checkbox(&mut variable_rate, tr("Variable Rate"))
+            .changed()
+        {
+            *schedule = variable_rate.then(RateSchedule::default);
+            modified = true;
+        }
+        if let Some(schedule) = schedule {
+            ui.push_id(id_salt, |ui| {
+                modified |= schedule.ui_edit(ui, base_rate, start_date, rates);
+            });
+        }
+        modified
+    }
+
+    /// Edits the list of rate changes, each within `rates`. `start_date` is used to place the
+    /// first change. Returns true if the schedule was changed.
+    pub fn ui_edit(
+        &mut self,
+        ui: &mut Ui,
+        base_rate: f32,
+        start_date: NaiveDate,
+        rates: RangeInclusive<f32>,
+    ) -> bool {
+        let mut modified = false;
+        let mut index_to_remove = None;
+
+        for (index, change) in self.changes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                modified |= date_edit(ui, &mut change.date);
+                modified |= ui
+                    .add(
+                        number_drag_value(&mut change.rate_per_year)
+                            .speed(0.1)
+                            .range(rates.clone())
+                            .suffix(" %"),
+                    )
+                    .changed();
+                if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
+                    index_to_remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = index_to_remove {
+            self.changes.remove(index);
+            modified = true;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("Add Rate Change")).clicked() {
+                // Renew after the last change, keeping its rate until edited.
+                let (last_date, last_rate) = self
+                    .changes
+                    .iter()
+                    .max_by_key(|change| change.date)
+                    .map_or((start_date, base_rate), |change| {
+                        (change.date, change.rate_per_year)
+                    });
+                self.changes.push(RateChange {
+                    date: last_date + Months::new(self.renewal_months.max(1)),
+                    rate_per_year: last_rate,
+                });
+                modified = true;
+            }
+            ui.label(tr("every"));
+            modified |= ui
+                .add(
+                    number_drag_value(&mut self.renewal_months)
+                        .range(1..=120)
+                        .suffix(format!(" {}", tr("months"))),
+                )
+                .changed();
+        });
+
+        modified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn schedule(changes: &[(NaiveDate, f32)]) -> RateSchedule {
+        RateSchedule {
+            changes: changes
+                .iter()
+                .map(|&(date, rate_per_year)| RateChange {
+                    date,
+                    rate_per_year,
+                })
+                .collect(),
+            ..RateSchedule::default()
+        }
+    }
+
+    #[test]
+    fn finds_the_rate_in_effect() {
+        // Stored out of order on purpose.
+        let schedule = schedule(&[(date(2026, 1, 1), 2.0), (date(2025, 1, 1), 4.0)]);
+        assert_eq!(schedule.rate_at(5.0, date(2024, 12, 31)), 5.0);
+        assert_eq!(schedule.rate_at(5.0, date(2025, 1, 1)), 4.0);
+        assert_eq!(schedule.rate_at(5.0, date(2025, 12, 31)), 4.0);
+        assert_eq!(schedule.rate_at(5.0, date(2030, 1, 1)), 2.0);
+    }
+
+    #[test]
+    fn compounds_each_rate_over_its_days() {
+        let schedule = schedule(&[(date(2026, 1, 1), 0.0), (date(2025, 1, 1), 10.0)]);
+        let multiplier = schedule.growth_multiplier(0.0, date(2024, 1, 1), date(2027, 1, 1));
+        assert!((multiplier - 1.1).abs() < 1e-5, "{multiplier}");
+
+        // Half a year at the base rate, then half a year at 10%.
+        let multiplier = schedule.growth_multiplier(0.0, date(2024, 7, 2), date(2025, 7, 2));
+        assert!((multiplier - 1.1_f32.powf(182.0 / 365.0)).abs() < 1e-5);
+
+        assert_eq!(
+            schedule.growth_multiplier(10.0, date(2025, 1, 1), date(2024, 1, 1)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn applies_a_change_on_the_start_date() {
+        let schedule = schedule(&[(date(2025, 1, 1), 10.0)]);
+        let multiplier = schedule.growth_multiplier(0.0, date(2025, 1, 1), date(2026, 1, 1));
+        assert!((multiplier - 1.1).abs() < 1e-5, "{multiplier}");
+
+        // A change on the end date does not apply to the period before it.
+        let multiplier = schedule.growth_multiplier(0.0, date(2024, 1, 1), date(2025, 1, 1));
+        assert_eq!(multiplier, 1.0);
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDate;
+
+/// A recorded balance of an asset on a given date.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Snapshot {
+    pub date: NaiveDate,
+    pub value: f32,
+}
+
+/// The last recorded value on or before the given date. `snapshots` must be sorted by date.
+pub fn value_at(snapshots: &[Snapshot], date: NaiveDate) -> Option<f32> {
+    snapshots
+        .iter()
+        .take_while(|snapshot| snapshot.date <= date)
+        .last()
+        .map(|snapshot| snapshot.value)
+}
+
+/// Adds snapshots to a sorted list, replacing any existing snapshot on the same date.
+/// Returns the number of snapshots added or replaced.
+pub fn merge_snapshots(
+    snapshots: &mut Vec<Snapshot>,
+    new_snapshots: impl IntoIterator<Item = Snapshot>,
+) -> usize {
+    let mut merged = 0;
+    for snapshot in new_snapshots {
+        match snapshots.binary_search_by_key(&snapshot.date, |existing| existing.date) {
+            Ok(index) => snapshots[index] = snapshot,
+            Err(index) => snapshots.insert(index, snapshot),
+        }
+        merged += 1;
+    }
+    merged
+}
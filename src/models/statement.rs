@@ -0,0 +1,482 @@
+use chrono::NaiveDate;
+
+use super::{csv::parse_amount, snapshot::Snapshot};
+
+/// A booked bank transaction. `id` is the bank's own reference (the OFX FITID or the camt.053
+/// entry reference), or one built from the contents if there is none, and is used to recognise
+/// transactions that were already imported.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Transaction {
+    pub id: String,
+    pub date: NaiveDate,
+    pub amount: f32, // positive for deposits, negative for withdrawals
+    pub description: String,
+}
+
+/// Bank statement file formats that can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatementFormat {
+    Ofx, // also QFX, which is OFX with a few extra tags
+    Camt053,
+}
+
+/// The transactions of a bank statement and, if the file states it, the closing balance.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statement {
+    pub transactions: Vec<Transaction>, // sorted by date
+    pub closing_balance: Option<Snapshot>,
+}
+
+impl Statement {
+    /// Guesses the format from the file contents.
+    pub fn detect_format(text: &str) -> Option<StatementFormat> {
+        if text.contains("BkToCstmrStmt") {
+            Some(StatementFormat::Camt053)
+        } else if text.contains("OFXHEADER") || text.to_uppercase().contains("<OFX>") {
+            Some(StatementFormat::Ofx)
+        } else {
+            None
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut statement = match Self::detect_format(text) {
+            Some(StatementFormat::Ofx) => parse_ofx(text),
+            Some(StatementFormat::Camt053) => parse_camt053(text),
+            None => return Err("Not an OFX, QFX or camt.053 file".to_owned()),
+        };
+        if statement.transactions.is_empty() {
+            return Err("The statement contains no transactions".to_owned());
+        }
+        statement
+            .transactions
+            .sort_by_key(|transaction| transaction.date);
+        Ok(statement)
+    }
+}
+
+/// A tag in an OFX or XML file together with the text directly following it.
+struct Tag {
+    name: String,
+    closing: bool,
+    text: String,
+}
+
+/// Splits SGML or XML markup into tags. Namespace prefixes, attributes, comments and
+/// processing instructions are dropped. Self-closing tags are skipped since they carry no text.
+fn tags(text: &str) -> Vec<Tag> {
+    text.split('<')
+        .skip(1)
+        .filter_map(|part| {
+            let (inside, after) = part.split_once('>')?;
+            if inside.starts_with('?') || inside.starts_with('!') || inside.ends_with('/') {
+                return None;
+            }
+            let closing = inside.starts_with('/');
+            let name = inside
+                .trim_start_matches('/')
+                .split_whitespace()
+                .next()?
+                .rsplit(':')
+                .next()?
+                .to_uppercase();
+            Some(Tag {
+                name,
+                closing,
+                text: decode_entities(after.trim()),
+            })
+        })
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// OFX dates look like `20240131`, `20240131120000` or `20240131120000.000[-5:EST]`.
+fn parse_ofx_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.get(..8)?, "%Y%m%d").ok()
+}
+
+/// camt.053 dates are either dates (`2024-01-31`) or date-times (`2024-01-31T12:00:00`).
+fn parse_iso_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Parses an OFX or QFX statement. Both the SGML (version 1) and XML (version 2) flavours
+/// are accepted, since leaf elements are read without relying on closing tags.
+pub fn parse_ofx(text: &str) -> Statement {
+    let mut statement = Statement::default();
+    let mut transaction: Option<Vec<(String, String)>> = None;
+    let mut in_ledger_balance = false;
+    let mut balance_amount = None;
+    let mut balance_date = None;
+
+    for tag in tags(text) {
+        match (tag.name.as_str(), tag.closing) {
+            ("STMTTRN", false) => transaction = Some(Vec::new()),
+            ("STMTTRN", true) => {
+                if let Some(fields) = transaction.take() {
+                    let transaction = ofx_transaction(&fields, &statement.transactions);
+                    statement.transactions.extend(transaction);
+                }
+            }
+            ("LEDGERBAL", closing) => in_ledger_balance = !closing,
+            (name, false) => {
+                if let Some(fields) = &mut transaction {
+                    fields.push((name.to_owned(), tag.text));
+                } else if in_ledger_balance && name == "BALAMT" {
+                    balance_amount = parse_amount(&tag.text);
+                } else if in_ledger_balance && name == "DTASOF" {
+                    balance_date = parse_ofx_date(&tag.text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(value), Some(date)) = (balance_amount, balance_date) {
+        statement.closing_balance = Some(Snapshot { date, value });
+    }
+    statement
+}
+
+fn ofx_transaction(fields: &[(String, String)], previous: &[Transaction]) -> Option<Transaction> {
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field_name, _)| field_name == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let date = parse_ofx_date(field("DTPOSTED")?)?;
+    let amount = parse_amount(field("TRNAMT")?)?;
+    let description = [field("NAME"), field("MEMO")]
+        .into_iter()
+        .flatten()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" - ");
+    let id = field("FITID").filter(|id| !id.is_empty()).map_or_else(
+        || fallback_id(date, amount, &description, previous),
+        str::to_owned,
+    );
+    Some(Transaction {
+        id,
+        date,
+        amount,
+        description,
+    })
+}
+
+/// Fields of a camt.053 entry (`Ntry`) or balance (`Bal`) element.
+#[derive(Default)]
+struct CamtFields {
+    amount: Option<f32>,
+    credit: bool,
+    date: Option<NaiveDate>,
+    reference: Option<String>,
+    description: Option<String>,
+    balance_type: Option<String>,
+}
+
+/// Parses an ISO 20022 camt.053 bank-to-customer statement. The closing balance is taken
+/// from the `CLBD` balance of the last statement in the file.
+pub fn parse_camt053(text: &str) -> Statement {
+    let mut statement = Statement::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut entry: Option<CamtFields> = None;
+    let mut balance: Option<CamtFields> = None;
+
+    for tag in tags(text) {
+        if tag.closing {
+            if let Some(index) = path.iter().rposition(|name| *name == tag.name) {
+                path.truncate(index);
+            }
+            match tag.name.as_str() {
+                "NTRY" => {
+                    if let Some(fields) = entry.take() {
+                        let transaction = camt_transaction(fields, &statement.transactions);
+                        statement.transactions.extend(transaction);
+                    }
+                }
+                "BAL" => {
+                    if let Some(fields) = balance.take() {
+                        if let (Some("CLBD"), Some(amount), Some(date)) =
+                            (fields.balance_type.as_deref(), fields.amount, fields.date)
+                        {
+                            let value = if fields.credit { amount } else { -amount };
+                            statement.closing_balance = Some(Snapshot { date, value });
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let parent = path.last().map(String::as_str).unwrap_or_default();
+        match (parent, tag.name.as_str()) {
+            (_, "NTRY") => entry = Some(CamtFields::default()),
+            (_, "BAL") if entry.is_none() => balance = Some(CamtFields::default()),
+            _ => {
+                if let Some(fields) = entry.as_mut().or(balance.as_mut()) {
+                    read_camt_field(fields, parent, &tag);
+                }
+            }
+        }
+        path.push(tag.name);
+    }
+
+    statement
+}
+
+fn read_camt_field(fields: &mut CamtFields, parent: &str, tag: &Tag) {
+    match (parent, tag.name.as_str()) {
+        // Only the amount and indicator of the entry itself, not of its transaction details.
+        ("NTRY" | "BAL", "AMT") => fields.amount = parse_amount(&tag.text),
+        ("NTRY" | "BAL", "CDTDBTIND") => fields.credit = tag.text == "CRDT",
+        ("BOOKGDT" | "DT", "DT" | "DTTM") => {
+            if fields.date.is_none() {
+                fields.date = parse_iso_date(&tag.text);
+            }
+        }
+        ("VALDT", "DT" | "DTTM") => {
+            fields.date = fields.date.or_else(|| parse_iso_date(&tag.text));
+        }
+        ("NTRY", "ACCTSVCRREF") => fields.reference = Some(tag.text.clone()),
+        ("NTRY", "NTRYREF") | ("REFS", "ACCTSVCRREF" | "ENDTOENDID") => {
+            fields.reference.get_or_insert_with(|| tag.text.clone());
+        }
+        ("NTRY", "ADDTLNTRYINF") => fields.description = Some(tag.text.clone()),
+        ("RMTINF", "USTRD") => {
+            fields.description.get_or_insert_with(|| tag.text.clone());
+        }
+        ("CDORPRTRY", "CD") => fields.balance_type = Some(tag.text.clone()),
+        _ => {}
+    }
+}
+
+fn camt_transaction(fields: CamtFields, previous: &[Transaction]) -> Option<Transaction> {
+    let date = fields.date?;
+    let amount = if fields.credit {
+        fields.amount?
+    } else {
+        -fields.amount?
+    };
+    let description = fields.description.unwrap_or_default();
+    let id = fields
+        .reference
+        .filter(|reference| !reference.is_empty() && reference != "NOTPROVIDED")
+        .unwrap_or_else(|| fallback_id(date, amount, &description, previous));
+    Some(Transaction {
+        id,
+        date,
+        amount,
+        description,
+    })
+}
+
+/// Identifies transactions without a bank reference by their contents. Identical transactions
+/// earlier in the same statement are counted, so that two equal purchases on the same day
+/// both get imported.
+fn fallback_id(
+    date: NaiveDate,
+    amount: f32,
+    description: &str,
+    previous: &[Transaction],
+) -> String {
+    let id = format!("{date}/{amount:.2}/{description}");
+    let occurrence = previous
+        .iter()
+        .filter(|transaction| {
+            transaction.date == date
+                && transaction.amount == amount
+                && transaction.description == description
+        })
+        .count();
+    if occurrence == 0 {
+        id
+    } else {
+        format!("{id}/{occurrence}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cash;
+
+    const OFX: &str = "OFXHEADER:100
+DATA:OFXSGML
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20240131120000.000[-5:EST]
+<TRNAMT>2500.00
+<FITID>A1
+<NAME>Salary
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20240105
+<TRNAMT>-45.50
+<NAME>Groceries
+<MEMO>Corner &amp; Co
+</STMTTRN>
+</BANKTRANLIST>
+<LEDGERBAL>
+<BALAMT>3154.50
+<DTASOF>20240131
+</LEDGERBAL>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>";
+
+    const CAMT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Bal>
+        <Tp><CdOrPrtry><Cd>CLBD</Cd></CdOrPrtry></Tp>
+        <Amt Ccy="EUR">1200.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Dt><Dt>2024-02-29</Dt></Dt>
+      </Bal>
+      <Ntry>
+        <Amt Ccy="EUR">300.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <BookgDt><Dt>2024-02-10</Dt></BookgDt>
+        <AcctSvcrRef>REF-1</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <AmtDtls><TxAmt><Amt Ccy="EUR">999.00</Amt></TxAmt></AmtDtls>
+          <RmtInf><Ustrd>Rent</Ustrd></RmtInf>
+        </TxDtls></NtryDtls>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">50.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <BookgDt><DtTm>2024-02-01T09:30:00</DtTm></BookgDt>
+        <AcctSvcrRef>NOTPROVIDED</AcctSvcrRef>
+        <AddtlNtryInf>Refund</AddtlNtryInf>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parses_ofx() {
+        let statement = Statement::parse(OFX).unwrap();
+        assert_eq!(
+            statement.transactions,
+            vec![
+                Transaction {
+                    id: fallback_id(date(2024, 1, 5), -45.5, "Groceries - Corner & Co", &[]),
+                    date: date(2024, 1, 5),
+                    amount: -45.5,
+                    description: "Groceries - Corner & Co".to_owned(),
+                },
+                Transaction {
+                    id: "A1".to_owned(),
+                    date: date(2024, 1, 31),
+                    amount: 2500.0,
+                    description: "Salary".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            statement.closing_balance,
+            Some(Snapshot {
+                date: date(2024, 1, 31),
+                value: 3154.5,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_camt053() {
+        let statement = Statement::parse(CAMT).unwrap();
+        assert_eq!(
+            statement.transactions,
+            vec![
+                Transaction {
+                    id: fallback_id(date(2024, 2, 1), 50.0, "Refund", &[]),
+                    date: date(2024, 2, 1),
+                    amount: 50.0,
+                    description: "Refund".to_owned(),
+                },
+                Transaction {
+                    id: "REF-1".to_owned(),
+                    date: date(2024, 2, 10),
+                    amount: -300.0,
+                    description: "Rent".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            statement.closing_balance,
+            Some(Snapshot {
+                date: date(2024, 2, 29),
+                value: 1200.0,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_and_empty_statements() {
+        assert!(Statement::parse("date,amount\n2024-01-01,10").is_err());
+        assert!(Statement::parse("<OFX></OFX>").is_err());
+    }
+
+    #[test]
+    fn skips_already_imported_transactions() {
+        let mut cash = Cash::default();
+        let statement = Statement::parse(OFX).unwrap();
+        assert_eq!(cash.import_statement(&statement), 2);
+        assert_eq!(cash.import_statement(&statement), 0);
+
+        // The same transactions without bank references are recognised by their contents.
+        let without_ids = Statement::parse(&OFX.replace("<FITID>A1\n", "")).unwrap();
+        let mut cash = Cash::default();
+        assert_eq!(cash.import_statement(&without_ids), 2);
+        assert_eq!(cash.import_statement(&without_ids), 0);
+    }
+
+    #[test]
+    fn imports_identical_transactions_without_references() {
+        let groceries =
+            "<STMTTRN>\n<DTPOSTED>20240105\n<TRNAMT>-45.50\n<NAME>Groceries\n</STMTTRN>\n";
+        let twice = OFX.replace(
+            "<BANKTRANLIST>\n",
+            &format!("<BANKTRANLIST>\n{groceries}{groceries}"),
+        );
+        let statement = Statement::parse(&twice).unwrap();
+        let ids: Vec<_> = statement
+            .transactions
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "2024-01-05/-45.50/Groceries",
+                "2024-01-05/-45.50/Groceries/1",
+                "2024-01-05/-45.50/Groceries - Corner & Co",
+                "A1"
+            ]
+        );
+
+        let mut cash = Cash::default();
+        assert_eq!(cash.import_statement(&statement), 4);
+        assert_eq!(cash.import_statement(&statement), 0);
+    }
+}
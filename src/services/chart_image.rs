@@ -0,0 +1,174 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::{
+    export::{hex_color, PlotSeries},
+    format::format_compact,
+    i18n::tr,
+};
+
+const WIDTH: f32 = 1000.0;
+const HEIGHT: f32 = 600.0;
+const MARGIN_LEFT: f32 = 90.0;
+const MARGIN_RIGHT: f32 = 220.0; // room for the legend
+const MARGIN_TOP: f32 = 50.0;
+const MARGIN_BOTTOM: f32 = 50.0;
+const FONT: &str = "font-family=\"sans-serif\" font-size=\"12\"";
+
+/// Draws the series as an SVG line chart with axes, grid lines, a legend and the currency on
+/// the value axis. Colors and names are the same as in the on-screen plot.
+pub fn series_to_svg(series: &[PlotSeries], title: &str, currency: &str) -> String {
+    let dates = series.first().map_or(&[][..], |line| &line.points[..]);
+    let (Some((first_date, _)), Some((last_date, _))) = (dates.first(), dates.last()) else {
+        return empty_svg(title);
+    };
+    let values = series
+        .iter()
+        .flat_map(|line| line.points.iter().map(|(_, value)| *value));
+    let min_value = values.clone().fold(0.0f32, f32::min);
+    let max_value = values.fold(0.0f32, f32::max);
+    let step = nice_step((max_value - min_value).max(1.0) / 6.0);
+    let y_min = (min_value / step).floor() * step;
+    let mut y_max = (max_value / step).ceil() * step;
+    if y_max == y_min {
+        // All values are zero, e.g. for an empty portfolio.
+        y_max = y_min + step;
+    }
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let total_days = (*last_date - *first_date).num_days().max(1) as f32;
+    let x = |date: NaiveDate| {
+        MARGIN_LEFT + (date - *first_date).num_days() as f32 / total_days * plot_width
+    };
+    let y = |value: f32| MARGIN_TOP + (y_max - value) / (y_max - y_min) * plot_height;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\">\n"
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{WIDTH}\" height=\"{HEIGHT}\" fill=\"white\"/>\n"
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{MARGIN_LEFT}\" y=\"30\" font-family=\"sans-serif\" font-size=\"18\">{}</text>\n",
+        escape_xml(title)
+    ));
+
+    // Value axis: grid lines and labels.
+    let mut value = y_min;
+    while value <= y_max + step / 2.0 {
+        svg.push_str(&format!(
+            "<line x1=\"{MARGIN_LEFT}\" y1=\"{0:.1}\" x2=\"{1:.1}\" y2=\"{0:.1}\" stroke=\"#dddddd\"/>\n",
+            y(value),
+            MARGIN_LEFT + plot_width
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" {FONT} text-anchor=\"end\">{}</text>\n",
+            MARGIN_LEFT - 8.0,
+            y(value) + 4.0,
+            format_compact(value as f64)
+        ));
+        value += step;
+    }
+    svg.push_str(&format!(
+        "<text transform=\"translate(20 {:.1}) rotate(-90)\" {FONT} text-anchor=\"middle\">{} ({})</text>\n",
+        MARGIN_TOP + plot_height / 2.0,
+        tr("Value"),
+        escape_xml(currency)
+    ));
+
+    // Date axis: a label at the start of each year, thinned out to at most ten labels.
+    let years = (first_date.year()..=last_date.year())
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, 1, 1))
+        .filter(|date| first_date <= date && date <= last_date)
+        .collect::<Vec<_>>();
+    let year_step = years.len().div_ceil(10).max(1);
+    for date in years.iter().step_by(year_step) {
+        svg.push_str(&format!(
+            "<line x1=\"{0:.1}\" y1=\"{MARGIN_TOP}\" x2=\"{0:.1}\" y2=\"{1:.1}\" stroke=\"#eeeeee\"/>\n",
+            x(*date),
+            MARGIN_TOP + plot_height
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" {FONT} text-anchor=\"middle\">{}</text>\n",
+            x(*date),
+            MARGIN_TOP + plot_height + 20.0,
+            date.year()
+        ));
+    }
+    svg.push_str(&format!(
+        "<rect x=\"{MARGIN_LEFT}\" y=\"{MARGIN_TOP}\" width=\"{plot_width}\" height=\"{plot_height}\" fill=\"none\" stroke=\"#888888\"/>\n"
+    ));
+
+    // The lines, with the portfolio total (last) drawn on top.
+    for line in series {
+        let points: Vec<String> = line
+            .points
+            .iter()
+            .map(|(date, value)| format!("{:.1},{:.1}", x(*date), y(*value)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            points.join(" "),
+            hex_color(line.color)
+        ));
+    }
+
+    // Legend.
+    let legend_x = MARGIN_LEFT + plot_width + 20.0;
+    for (index, line) in series.iter().enumerate() {
+        let legend_y = MARGIN_TOP + 10.0 + index as f32 * 20.0;
+        svg.push_str(&format!(
+            "<rect x=\"{legend_x:.1}\" y=\"{:.1}\" width=\"14\" height=\"4\" fill=\"{}\"/>\n",
+            legend_y - 4.0,
+            hex_color(line.color)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{legend_y:.1}\" {FONT}>{}</text>\n",
+            legend_x + 20.0,
+            escape_xml(line.display_name())
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn empty_svg(title: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\">\
+         <text x=\"{MARGIN_LEFT}\" y=\"30\" {FONT}>{}</text></svg>\n",
+        escape_xml(title)
+    )
+}
+
+/// The smallest 1, 2 or 5 times a power of ten that is at least `step`.
+fn nice_step(step: f32) -> f32 {
+    let magnitude = 10f32.powf(step.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|nice| *nice >= step)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Escapes text for use in XML and HTML markup.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Encodes an image, such as a screenshot of the plot, as PNG.
+pub fn encode_png(image: &egui::ColorImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, image.width() as u32, image.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+    writer
+        .write_image_data(image.as_raw())
+        .map_err(|error| error.to_string())?;
+    writer.finish().map_err(|error| error.to_string())?;
+    Ok(bytes)
+}
@@ -0,0 +1,337 @@
+use chrono::NaiveDate;
+
+use crate::{
+    asset::AssetTrait,
+    i18n::tr,
+    plot_utils::{get_portfolio_value_points, get_value_points_for_asset},
+    Portfolio,
+};
+
+/// Name of the portfolio total series. Series names are kept in English so that exported
+/// files do not depend on the UI language; see [`PlotSeries::display_name`].
+pub const PORTFOLIO_TOTAL: &str = "Portfolio Total";
+/// Name of the series summing the assets without a group, kept in English like
+/// [`PORTFOLIO_TOTAL`].
+pub const UNGROUPED: &str = "Ungrouped";
+
+/// One line of the wealth plot: its legend name, color and the values it is drawn from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotSeries {
+    pub name: String,
+    pub color: egui::Color32,
+    pub points: Vec<(NaiveDate, f32)>,
+}
+
+impl PlotSeries {
+    /// The name shown on screen. The fixed series names are translated, asset and group names
+    /// are shown as they are.
+    pub fn display_name(&self) -> &str {
+        match self.name.as_str() {
+            PORTFOLIO_TOTAL | UNGROUPED => tr(&self.name),
+            name => name,
+        }
+    }
+}
+
+/// The series drawn in the central plot: one per asset followed by the portfolio total.
+pub fn plot_series(
+    portfolio: &Portfolio,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    interval_days: i64,
+) -> Vec<PlotSeries> {
+    let mut series: Vec<PlotSeries> = portfolio
+        .assets
+        .iter()
+        .map(|asset| PlotSeries {
+            name: asset.name(),
+            color: asset.color(),
+            points: get_value_points_for_asset(
+                portfolio,
+                asset,
+                start_date,
+                end_date,
+                interval_days,
+            ),
+        })
+        .collect();
+    series.push(PlotSeries {
+        name: PORTFOLIO_TOTAL.to_owned(),
+        color: egui::Color32::LIGHT_BLUE,
+        points: get_portfolio_value_points(portfolio, start_date, end_date, interval_days),
+    });
+    series
+}
+
+/// One series per group, summing the contributions to net worth of the group's assets,
+/// followed by the portfolio total. Assets without a group are summed as "Ungrouped". Each
+/// group has the color of its first asset.
+pub fn group_series(
+    portfolio: &Portfolio,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    interval_days: i64,
+) -> Vec<PlotSeries> {
+    let mut groups = portfolio.groups();
+    if portfolio
+        .assets
+        .iter()
+        .any(|asset| asset.meta().group.is_empty())
+    {
+        groups.push(String::new());
+    }
+    let mut dates = Vec::new();
+    let mut date = start_date;
+    while date <= end_date {
+        dates.push(date);
+        date += chrono::Duration::days(interval_days);
+    }
+
+    let mut series: Vec<PlotSeries> = groups
+        .iter()
+        .map(|group| {
+            let assets: Vec<_> = portfolio
+                .assets
+                .iter()
+                .filter(|asset| &asset.meta().group == group)
+                .collect();
+            let points = dates
+                .iter()
+                .map(|&date| {
+                    let value = assets
+                        .iter()
+                        .map(|asset| portfolio.signed_asset_value(asset, date))
+                        .sum();
+                    (date, value)
+                })
+                .collect();
+            PlotSeries {
+                name: if group.is_empty() {
+                    UNGROUPED.to_owned()
+                } else {
+                    group.clone()
+                },
+                color: assets[0].color(),
+                points,
+            }
+        })
+        .collect();
+    series.push(PlotSeries {
+        name: PORTFOLIO_TOTAL.to_owned(),
+        color: egui::Color32::LIGHT_BLUE,
+        points: get_portfolio_value_points(portfolio, start_date, end_date, interval_days),
+    });
+    series
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn export(&self, series: &[PlotSeries]) -> String {
+        match self {
+            ExportFormat::Csv => series_to_csv(series),
+            ExportFormat::Json => series_to_json(series),
+        }
+    }
+}
+
+/// One row per date with a column per series. All series share the plot's dates. The header
+/// holds the untranslated series names.
+pub fn series_to_csv(series: &[PlotSeries]) -> String {
+    let mut csv = String::from("Date");
+    for line in series {
+        csv.push(',');
+        csv.push_str(&csv_field(&line.name));
+    }
+    csv.push('\n');
+
+    let dates = series.first().map_or(&[][..], |line| &line.points[..]);
+    for (index, (date, _)) in dates.iter().enumerate() {
+        csv.push_str(&date.to_string());
+        for line in series {
+            csv.push(',');
+            if let Some((_, value)) = line.points.get(index) {
+                csv.push_str(&format!("{:.2}", value));
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// A list of series, each with its name, hex color and date/value points.
+pub fn series_to_json(series: &[PlotSeries]) -> String {
+    let lines: Vec<String> = series
+        .iter()
+        .map(|line| {
+            let points: Vec<String> = line
+                .points
+                .iter()
+                .map(|(date, value)| {
+                    format!(
+                        "{{\"date\":\"{}\",\"value\":{}}}",
+                        date,
+                        json_number(*value)
+                    )
+                })
+                .collect();
+            format!(
+                "{{\"name\":{},\"color\":\"{}\",\"points\":[{}]}}",
+                json_string(&line.name),
+                hex_color(line.color),
+                points.join(",")
+            )
+        })
+        .collect();
+    format!("{{\"series\":[{}]}}\n", lines.join(","))
+}
+
+/// `#rrggbb` notation, ignoring transparency.
+pub fn hex_color(color: egui::Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// A number with two decimals, or `null` for NaN and infinity, which JSON cannot express.
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{:.2}", value)
+    } else {
+        "null".to_owned()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Writes the file to the working directory and returns where it was saved.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(file_name: &str, contents: &[u8], _mime_type: &str) -> Result<String, String> {
+    let path = std::env::current_dir()
+        .map_err(|error| error.to_string())?
+        .join(file_name);
+    std::fs::write(&path, contents).map_err(|error| error.to_string())?;
+    Ok(format!("{} {}", tr("Saved"), path.display()))
+}
+
+/// Lets the browser download the file.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(file_name: &str, contents: &[u8], mime_type: &str) -> Result<String, String> {
+    use eframe::wasm_bindgen::JsCast as _;
+
+    let error = |error: eframe::wasm_bindgen::JsValue| format!("{:?}", error);
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob =
+        web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let anchor = document
+        .create_element("a")
+        .map_err(error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "Could not create a download link")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url).map_err(error)?;
+    Ok(format!("{} {}", tr("Downloaded"), file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv::{parse_amount, parse_date, parse_rows};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn series() -> Vec<PlotSeries> {
+        let dates = [date(2024, 1, 1), date(2024, 1, 31)];
+        vec![
+            PlotSeries {
+                name: "House, \"Main\"".to_owned(),
+                color: egui::Color32::from_rgb(255, 0, 16),
+                points: vec![(dates[0], 1000.0), (dates[1], 1012.25)],
+            },
+            PlotSeries {
+                name: PORTFOLIO_TOTAL.to_owned(),
+                color: egui::Color32::LIGHT_BLUE,
+                points: vec![(dates[0], -50.5), (dates[1], f32::NAN)],
+            },
+        ]
+    }
+
+    #[test]
+    fn reads_back_exported_csv() {
+        let rows = parse_rows(&series_to_csv(&series()));
+        assert_eq!(rows[0], vec!["Date", "House, \"Main\"", "Portfolio Total"]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(parse_date(&rows[1][0]), Some(date(2024, 1, 1)));
+        assert_eq!(parse_amount(&rows[1][1]), Some(1000.0));
+        assert_eq!(parse_amount(&rows[1][2]), Some(-50.5));
+        assert_eq!(parse_date(&rows[2][0]), Some(date(2024, 1, 31)));
+        assert_eq!(parse_amount(&rows[2][1]), Some(1012.25));
+    }
+
+    #[test]
+    fn exports_json_series() {
+        assert_eq!(
+            series_to_json(&series()),
+            concat!(
+                "{\"series\":[",
+                "{\"name\":\"House, \\\"Main\\\"\",\"color\":\"#ff0010\",\"points\":[",
+                "{\"date\":\"2024-01-01\",\"value\":1000.00},",
+                "{\"date\":\"2024-01-31\",\"value\":1012.25}]},",
+                "{\"name\":\"Portfolio Total\",\"color\":\"#add8e6\",\"points\":[",
+                "{\"date\":\"2024-01-01\",\"value\":-50.50},",
+                "{\"date\":\"2024-01-31\",\"value\":null}]}",
+                "]}\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use crate::i18n::{locale, Locale};
+
+/// Whole amounts with thousands separators and the currency where the locale puts it, e.g.
+/// `SEK 1,234,567` or `-$1,500` in English and `1 234 567 kr` in Swedish.
+pub fn format_amount(value: f32, currency: &str) -> String {
+    format_amount_with_decimals(value, currency, 0)
+}
+
+/// Like [`format_amount`], with a fixed number of decimals, e.g. for prices.
+pub fn format_amount_with_decimals(value: f32, currency: &str, decimals: usize) -> String {
+    let number = format_number(value as f64, decimals);
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", number.as_str()),
+    };
+    let symbol = currency_symbol(currency);
+    match locale() {
+        Locale::English if symbol.chars().count() == 1 => format!("{}{}{}", sign, symbol, number),
+        Locale::English => format!("{}{} {}", sign, symbol, number),
+        Locale::Swedish => format!("{}{} {}", sign, number, symbol),
+    }
+}
+
+/// A number with the locale's thousands separator and decimal mark, e.g. `1,234.50` or
+/// `1 234,50`.
+pub fn format_number(value: f64, decimals: usize) -> String {
+    let (thousands, decimal_mark) = separators();
+    let text = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut grouped = String::new();
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(thousands);
+        }
+        grouped.push(digit);
+    }
+    if !fraction.is_empty() {
+        grouped.push(decimal_mark);
+        grouped.push_str(fraction);
+    }
+    let is_zero = text.chars().all(|c| c == '0' || c == '.');
+    if value < 0.0 && !is_zero {
+        format!("-{}", grouped)
+    } else {
+        grouped
+    }
+}
+
+/// Short labels for chart axes, such as `250k` and `1.2M`, or `250 tn` and `1,2 mn` in Swedish.
+pub fn format_compact(value: f64) -> String {
+    let magnitude = value.abs();
+    let (scaled, suffix) = if magnitude >= 1_000_000.0 {
+        (value / 1_000_000.0, ["M", " mn"])
+    } else if magnitude >= 1_000.0 {
+        (value / 1_000.0, ["k", " tn"])
+    } else {
+        (value, ["", ""])
+    };
+    let number = format_number(scaled, 2);
+    let (_, decimal_mark) = separators();
+    let number = if number.contains(decimal_mark) {
+        number.trim_end_matches('0').trim_end_matches(decimal_mark)
+    } else {
+        &number
+    };
+    match locale() {
+        Locale::English => format!("{}{}", number, suffix[0]),
+        Locale::Swedish => format!("{}{}", number, suffix[1]),
+    }
+}
+
+/// Reads a number typed in the locale's notation. Thousands separators, spaces and currency
+/// symbols are ignored. In Swedish `.` is taken as decimal mark too, unless there is a `,`
+/// as in `1.234,5`.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let decimal_marks: &[char] = match locale() {
+        Locale::English => &['.'],
+        Locale::Swedish if text.contains(',') => &[','],
+        Locale::Swedish => &[',', '.'],
+    };
+    let number: String = text
+        .chars()
+        .filter_map(|c| match c {
+            '0'..='9' | '-' => Some(c),
+            c if decimal_marks.contains(&c) => Some('.'),
+            _ => None,
+        })
+        .collect();
+    number.parse().ok()
+}
+
+/// The symbol shown for a currency code. Codes without a well-known symbol are shown as is.
+pub fn currency_symbol(currency: &str) -> &str {
+    match (locale(), currency) {
+        (Locale::English, "USD") => "$",
+        (Locale::English, "GBP") => "£",
+        (Locale::English, "JPY") => "¥",
+        (_, "EUR") => "€",
+        (Locale::Swedish, "SEK") => "kr",
+        _ => currency,
+    }
+}
+
+/// The thousands separator and decimal mark of the current locale.
+fn separators() -> (char, char) {
+    match locale() {
+        Locale::English => (',', '.'),
+        Locale::Swedish => (' ', ','),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::i18n::set_locale;
+
+    /// The locale is global, so tests that set it take turns.
+    static LOCALE: Mutex<()> = Mutex::new(());
+
+    fn with_locale(locale: Locale, test: impl FnOnce()) {
+        let _guard = LOCALE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        set_locale(locale);
+        test();
+    }
+
+    #[test]
+    fn formats_numbers() {
+        with_locale(Locale::English, || {
+            assert_eq!(format_number(1234567.891, 2), "1,234,567.89");
+            assert_eq!(format_number(123456.0, 0), "123,456");
+            assert_eq!(format_number(999.5, 0), "1,000");
+            assert_eq!(format_number(-1500.0, 0), "-1,500");
+            assert_eq!(format_number(-0.004, 2), "0.00");
+            assert_eq!(format_number(-0.0, 0), "0");
+        });
+        with_locale(Locale::Swedish, || {
+            assert_eq!(format_number(1234567.891, 2), "1 234 567,89");
+            assert_eq!(format_number(-1500.0, 0), "-1 500");
+        });
+    }
+
+    #[test]
+    fn formats_amounts() {
+        with_locale(Locale::English, || {
+            assert_eq!(format_amount(-1500.0, "USD"), "-$1,500");
+            assert_eq!(format_amount(1234567.0, "SEK"), "SEK 1,234,567");
+            assert_eq!(format_amount_with_decimals(9.5, "EUR", 2), "€9.50");
+        });
+        with_locale(Locale::Swedish, || {
+            assert_eq!(format_amount(1234567.0, "SEK"), "1 234 567 kr");
+            assert_eq!(format_amount(-1500.0, "USD"), "-1 500 USD");
+        });
+    }
+
+    #[test]
+    fn formats_compact_numbers() {
+        with_locale(Locale::English, || {
+            assert_eq!(format_compact(1_250_000.0), "1.25M");
+            assert_eq!(format_compact(1_000_000.0), "1M");
+            assert_eq!(format_compact(250_000.0), "250k");
+            assert_eq!(format_compact(1_500.5), "1.5k");
+            assert_eq!(format_compact(-2_500.0), "-2.5k");
+            assert_eq!(format_compact(999.0), "999");
+            assert_eq!(format_compact(0.0), "0");
+        });
+        with_locale(Locale::Swedish, || {
+            assert_eq!(format_compact(1_250_000.0), "1,25 mn");
+            assert_eq!(format_compact(250_000.0), "250 tn");
+        });
+    }
+
+    #[test]
+    fn parses_numbers() {
+        with_locale(Locale::English, || {
+            assert_eq!(parse_number("1,234.5"), Some(1234.5));
+            assert_eq!(parse_number("$-45"), Some(-45.0));
+            assert_eq!(parse_number("abc"), None);
+        });
+        with_locale(Locale::Swedish, || {
+            assert_eq!(parse_number("1.234,5"), Some(1234.5));
+            assert_eq!(parse_number("1 234,5"), Some(1234.5));
+            assert_eq!(parse_number("12.5"), Some(12.5));
+            assert_eq!(parse_number("1 234 kr"), Some(1234.0));
+        });
+    }
+}
@@ -0,0 +1,62 @@
+use crate::Portfolio;
+
+/// Most undo steps kept; older ones are dropped.
+const MAX_STEPS: usize = 100;
+
+/// Undo and redo of portfolio edits. Each step is a copy of the whole portfolio, taken when
+/// the portfolio is seen to have changed.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Portfolio>,
+    redo: Vec<Portfolio>,
+    current: Option<Portfolio>, // the portfolio as last recorded
+}
+
+impl History {
+    /// Records the portfolio as an undo step if it changed since the last call. Returns true if
+    /// a step was recorded.
+    pub fn record(&mut self, portfolio: &Portfolio) -> bool {
+        match &self.current {
+            Some(current) if current == portfolio => false,
+            Some(_) => {
+                if let Some(previous) = self.current.replace(portfolio.clone()) {
+                    self.undo.push(previous);
+                }
+                if self.undo.len() > MAX_STEPS {
+                    self.undo.remove(0);
+                }
+                self.redo.clear();
+                true
+            }
+            None => {
+                self.current = Some(portfolio.clone());
+                false
+            }
+        }
+    }
+
+    /// Restores the portfolio to before the last recorded change.
+    pub fn undo(&mut self, portfolio: &mut Portfolio) {
+        if let Some(previous) = self.undo.pop() {
+            self.redo
+                .push(std::mem::replace(portfolio, previous.clone()));
+            self.current = Some(previous);
+        }
+    }
+
+    /// Reapplies the last undone change.
+    pub fn redo(&mut self, portfolio: &mut Portfolio) {
+        if let Some(next) = self.redo.pop() {
+            self.undo.push(std::mem::replace(portfolio, next.clone()));
+            self.current = Some(next);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
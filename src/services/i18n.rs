@@ -0,0 +1,337 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Language of the UI together with how numbers and amounts are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Locale {
+    #[default]
+    English, // 1,234.5 and SEK 1,234
+    Swedish, // 1 234,5 and 1 234 kr
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Swedish];
+
+    /// The name of the locale in its own language.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::Swedish => "Svenska",
+        }
+    }
+}
+
+// The locale is set once per frame from the application settings and read wherever text or
+// numbers are shown, so that it does not have to be passed through every widget.
+static LOCALE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::Swedish,
+        _ => Locale::English,
+    }
+}
+
+/// Translates English UI text into the current locale. Text without a translation, such as
+/// asset names, is returned unchanged. Enum names as printed by `{:?}` are translated too.
+pub fn tr(text: &str) -> &str {
+    match locale() {
+        Locale::English => english(text),
+        Locale::Swedish => swedish(text),
+    }
+}
+
+/// Enum names as printed by `{:?}` written out in English; other text is unchanged.
+fn english(text: &str) -> &str {
+    match text {
+        "RealEstate" => "Real Estate",
+        "CreditCard" => "Credit Card",
+        "EquityGrant" => "Equity Grant",
+        "SemiAnnual" => "Semi-Annual",
+        "StraightLine" => "Straight Line",
+        "DecliningBalance" => "Declining Balance",
+        "FixedResidual" => "Fixed Residual",
+        "Rsu" => "RSU",
+        "StockOption" => "Stock Option",
+        "OnTrack" => "On track",
+        _ => text,
+    }
+}
+
+fn swedish(text: &str) -> &str {
+    match text {
+        // Application
+        "Add Assets" => "Lägg till tillgångar",
+        "Select asset type" => "Välj tillgångstyp",
+        "Add Real Estate" => "Lägg till fastighet",
+        "Add Loan" => "Lägg till lån",
+        "Add Tradable" => "Lägg till värdepapper",
+        "Add Cash" => "Lägg till kontanter",
+        "Add Depreciating" => "Lägg till värdeminskande",
+        "Add Bond" => "Lägg till obligation",
+        "Add Credit Card" => "Lägg till kreditkort",
+        "Add Equity Grant" => "Lägg till aktietilldelning",
+        "Import CSV" => "Importera CSV",
+        "Import Bank Statement" => "Importera kontoutdrag",
+        "Application settings" => "Inställningar",
+        "Stroke width:" => "Linjebredd:",
+        "Resolution:" => "Upplösning:",
+        "End date:" => "Slutdatum:",
+        "Year" => "År",
+        "Month" => "Månad",
+        "Currency" => "Valuta",
+        "Language" => "Språk",
+        "Fees" => "Avgifter",
+        "Fees paid until" => "Betalda avgifter till och med",
+        "Total" => "Totalt",
+        "Goals" => "Mål",
+        "Add Goal" => "Lägg till mål",
+        "Annual Report" => "Årsrapport",
+        "Report Date: " => "Rapportdatum: ",
+        "Generate Report" => "Skapa rapport",
+        "Open the report in a browser and print it to save it as PDF." => {
+            "Öppna rapporten i en webbläsare och skriv ut den för att spara den som PDF."
+        }
+        "Assets" => "Tillgångar",
+        "Ledger" => "Transaktioner",
+        "Export" => "Exportera",
+        "Lines" => "Linjer",
+        "Stacked" => "Staplat",
+        "Pie" => "Cirkel",
+        "Bars" => "Staplar",
+        "Date: " => "Datum: ",
+        "Value" => "Värde",
+        "Net Worth" => "Nettoförmögenhet",
+        "Portfolio Total" => "Portföljen totalt",
+        "Saved" => "Sparade",
+        "Downloaded" => "Laddade ner",
+
+        "List" => "Lista",
+        "Table" => "Tabell",
+        "Name" => "Namn",
+        "Value at End" => "Värde vid slutet",
+        "Rate (%)" => "Ränta (%)",
+        "Per Month" => "Per månad",
+        "Share" => "Andel",
+
+        "Undo" => "Ångra",
+        "Undo (Ctrl+Z)" => "Ångra (Ctrl+Z)",
+        "Redo (Ctrl+Shift+Z)" => "Gör om (Ctrl+Skift+Z)",
+        "Deleted" => "Tog bort",
+
+        "Group: " => "Grupp: ",
+        "Tags: " => "Taggar: ",
+        "New tag" => "Ny tagg",
+        "Ungrouped" => "Utan grupp",
+        "By group" => "Per grupp",
+        "Tag: " => "Tagg: ",
+
+        "Drag to reorder" => "Dra för att flytta",
+        "Duplicate" => "Duplicera",
+        "Save as template" => "Spara som mall",
+        "From template" => "Från mall",
+        "Delete template" => "Ta bort mall",
+
+        // Asset types
+        "Real Estate" | "RealEstate" => "Fastighet",
+        "Loan" => "Lån",
+        "Tradable" => "Värdepapper",
+        "Cash" => "Kontanter",
+        "Depreciating" => "Värdeminskande",
+        "Bond" => "Obligation",
+        "Credit Card" | "CreditCard" => "Kreditkort",
+        "Equity Grant" | "EquityGrant" => "Aktietilldelning",
+
+        // Frequencies and models
+        "Weekly" => "Varje vecka",
+        "Monthly" => "Varje månad",
+        "Quarterly" => "Varje kvartal",
+        "Semi-Annual" | "SemiAnnual" => "Varje halvår",
+        "Annual" | "Yearly" => "Varje år",
+        "Never" => "Aldrig",
+        "Straight Line" | "StraightLine" => "Linjär",
+        "Declining Balance" | "DecliningBalance" => "Degressiv",
+        "Fixed Residual" | "FixedResidual" => "Fast restvärde",
+        "RSU" | "Rsu" => "RSU",
+        "Stock Option" | "StockOption" => "Aktieoption",
+
+        // Asset fields
+        "Value: " => "Värde: ",
+        "Annual Rate (%): " => "Årlig ränta (%): ",
+        "Acquisition Date: " => "Anskaffningsdatum: ",
+        "Monthly Rent: " => "Månadshyra: ",
+        "Monthly Maintenance: " => "Månatligt underhåll: ",
+        "Property Tax (%): " => "Fastighetsskatt (%): ",
+        "Yearly Fees: " => "Årliga avgifter: ",
+        "Planned Sale" => "Planerad försäljning",
+        "Sale Date: " => "Försäljningsdatum: ",
+        "Transaction Costs (%): " => "Transaktionskostnader (%): ",
+        "Capital Gains Tax (%): " => "Kapitalvinstskatt (%): ",
+        "Interest Rate (%): " => "Räntesats (%): ",
+        "Principal: " => "Amortering: ",
+        "Extra Payment: " => "Extra amortering: ",
+        "Frequency: " => "Frekvens: ",
+        "Paid Off: " => "Avbetalt: ",
+        "Contribution: " => "Insättning: ",
+        "Contribution Plan" => "Insättningsplan",
+        "Platform Fee (%): " => "Plattformsavgift (%): ",
+        "Expense Ratio (%): " => "Förvaltningsavgift (%): ",
+        "Dividends" => "Utdelningar",
+        "Dividend Yield (%): " => "Direktavkastning (%): ",
+        "Dividend Tax (%): " => "Utdelningsskatt (%): ",
+        "Reinvest Dividends" => "Återinvestera utdelningar",
+        "Holding" => "Innehav",
+        "Ticker: " => "Kortnamn: ",
+        "Shares: " => "Antal: ",
+        "Latest Price: " => "Senaste kurs: ",
+        "No prices" => "Inga kurser",
+        "Import Prices (date, price)" => "Importera kurser (datum, kurs)",
+        "Import" => "Importera",
+        "Clear History" => "Rensa historik",
+        "Clear" => "Rensa",
+        "Balance: " => "Saldo: ",
+        "Opening Balance: " => "Ingående saldo: ",
+        "Overdraft Limit: " => "Kredit: ",
+        "Overdraft Rate (%): " => "Kreditränta (%): ",
+        "Purchase Price: " => "Inköpspris: ",
+        "Model: " => "Modell: ",
+        "Depreciation Rate (%): " => "Avskrivningstakt (%): ",
+        "Useful Life (years): " => "Livslängd (år): ",
+        "Residual Value: " => "Restvärde: ",
+        "Face Value: " => "Nominellt belopp: ",
+        "Coupon Rate (%): " => "Kupongränta (%): ",
+        "Maturity Date: " => "Förfallodag: ",
+        "Credit Limit: " => "Kreditgräns: ",
+        "APR (%): " => "Effektiv ränta (%): ",
+        "Minimum Payment (%): " => "Lägsta betalning (%): ",
+        "Minimum Payment Floor: " => "Lägsta betalning minst: ",
+        "Interest Paid: " => "Betald ränta: ",
+        "Total Interest: " => "Total ränta: ",
+        "Type: " => "Typ: ",
+        "Grant Date: " => "Tilldelningsdatum: ",
+        "Share Price: " => "Aktiekurs: ",
+        "Price Growth (%): " => "Kursutveckling (%): ",
+        "Strike Price: " => "Lösenpris: ",
+        "Cliff (months): " => "Cliff (månader): ",
+        "Vesting (months): " => "Intjänande (månader): ",
+        "Tax at Vest (%): " => "Skatt vid intjänande (%): ",
+        "Pay out to: " => "Betala ut till: ",
+        "None" => "Ingen",
+        "Variable Rate" => "Rörlig ränta",
+        "Add Rate Change" => "Lägg till ränteändring",
+        "every" => "var",
+        "months" => "månader",
+        "End Date: " => "Slutdatum: ",
+        "to" => "till",
+        "Pause: " => "Paus: ",
+        "Add Pause" => "Lägg till paus",
+        "Yearly Increase (%): " => "Årlig ökning (%): ",
+        "Paid: " => "Betalt: ",
+
+        // Ledger
+        "Add Entry" => "Lägg till post",
+        "Note" => "Anteckning",
+        "Deposit" => "Insättning",
+        "Withdrawal" => "Uttag",
+        "Fee" => "Avgift",
+        "Dividend" => "Utdelning",
+        "Buy" => "Köp",
+        "Sell" => "Sälj",
+
+        // Goals
+        "New Goal" => "Nytt mål",
+        "Target: " => "Mål: ",
+        "By: " => "Senast: ",
+        "Reached" => "Uppnått",
+        "On track" | "OnTrack" => "I fas",
+        "Behind" => "Efter",
+
+        // Annual report
+        "Annual report for" => "Årsrapport för",
+        "Net worth on" => "Nettoförmögenhet den",
+        "Change since last year" => "Förändring sedan förra året",
+        "Fees paid during the year" => "Betalda avgifter under året",
+        "Asset" => "Tillgång",
+        "Type" => "Typ",
+        "Last year" => "Förra året",
+        "Change" => "Förändring",
+        "By Type" => "Per typ",
+        "Share of net worth" => "Andel av nettoförmögenheten",
+        "Projection" => "Prognos",
+        "Loans" => "Lån",
+        "Balance last year" => "Saldo förra året",
+        "Principal paid" => "Amorterat",
+        "Interest paid" => "Betald ränta",
+        "Paid off" => "Avbetalt",
+        "Not within 100 years" => "Inte inom 100 år",
+        "Goal" => "Mål",
+        "Target" => "Mål",
+        "By" => "Senast",
+        "Projected" => "Prognos",
+        "Status" => "Status",
+
+        // Allocation
+        "No assets" => "Inga tillgångar",
+        "Assets: " => "Tillgångar: ",
+        "Liabilities: " => "Skulder: ",
+        "Net worth: " => "Nettoförmögenhet: ",
+
+        // Import dialogs
+        "Paste CSV text below or drop a file onto the window." => {
+            "Klistra in CSV-text nedan eller släpp en fil i fönstret."
+        }
+        "Paste an OFX, QFX or camt.053 file below or drop it onto the window." => {
+            "Klistra in en OFX-, QFX- eller camt.053-fil nedan eller släpp den i fönstret."
+        }
+        "First row is a header" => "Första raden är en rubrik",
+        "Import into: " => "Importera till: ",
+        "Select asset" => "Välj tillgång",
+        "All" => "Alla",
+        "Ignore" => "Ignorera",
+        "Date" => "Datum",
+        "Amount" => "Belopp",
+        "Balance" => "Saldo",
+        "Ticker" => "Kortnamn",
+        "Price" => "Kurs",
+        "Select an asset to import into" => "Välj en tillgång att importera till",
+        "Select a cash asset to import into" => "Välj ett kontantkonto att importera till",
+        "Map a Balance or Amount column to import balances" => {
+            "Ange en kolumn för saldo eller belopp för att importera saldon"
+        }
+        "Map a Price column to import prices" => "Ange en kolumn för kurs för att importera kurser",
+        "Select which ticker to import" => "Välj vilket kortnamn som ska importeras",
+        "Select ticker" => "Välj kortnamn",
+        "Closing Balance: " => "Utgående saldo: ",
+        "Unknown" => "Okänt",
+        "transactions" => "transaktioner",
+        "Transactions imported into" => "Transaktioner importerade till",
+        "Already imported, skipped:" => "Redan importerade, överhoppade:",
+        "Rows imported into" => "Rader importerade till",
+        "rows" => "rader",
+        "with a valid date" => "med giltigt datum",
+        "History:" => "Historik:",
+        "balances" => "saldon",
+        "unvested" => "ej intjänat",
+        "Imported prices:" => "Importerade kurser:",
+        "No column is mapped to Date" => "Ingen kolumn är angiven som datum",
+        "Only cash assets and holdings can be imported into" => {
+            "Det går bara att importera till kontanter och innehav"
+        }
+        "No rows with a valid date and value were found" => {
+            "Inga rader med giltigt datum och värde hittades"
+        }
+        "No rows with a date and a price were found" => "Inga rader med datum och kurs hittades",
+        "Not an OFX, QFX or camt.053 file" => "Inte en OFX-, QFX- eller camt.053-fil",
+        "The statement contains no transactions" => "Kontoutdraget innehåller inga transaktioner",
+        "Set by the rate schedule" => "Bestäms av ränteschemat",
+        "Not used by the depreciation model" => "Används inte av avskrivningsmodellen",
+        "Set by the contribution plan" => "Bestäms av insättningsplanen",
+        "No closing balance; balances continue from the last recorded balance." => {
+            "Inget utgående saldo; saldot fortsätter från det senast registrerade saldot."
+        }
+        _ => english(text),
+    }
+}
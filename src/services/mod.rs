@@ -1,2 +1,8 @@
+pub mod chart_image;
+pub mod export;
+pub mod format;
+pub mod history;
+pub mod i18n;
 pub mod portfolio;
+pub mod report;
 pub use portfolio::Portfolio;
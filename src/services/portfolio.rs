@@ -1,83 +1,178 @@
-use crate::{asset::AssetTrait, plot_utils::get_value_points_for_asset, Asset};
+use crate::{
+    asset::AssetTrait,
+    goal::{Goal, GoalStatus},
+    Asset,
+};
 use chrono::NaiveDate;
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Portfolio {
     pub assets: Vec<Asset>,
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+}
+
+impl Default for Portfolio {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Portfolio {
     pub fn new() -> Self {
-        Self { assets: Vec::new() }
+        Self {
+            assets: Vec::new(),
+            goals: Vec::new(),
+        }
     }
 
     pub fn add_asset(&mut self, asset: Asset) {
         self.assets.push(asset);
     }
 
+    /// Removes the asset, and stops paying out into it from other assets.
     pub fn delete_asset(&mut self, uuid: uuid::Uuid) {
         self.assets.retain(|asset| asset.uuid() != uuid);
+        for asset in &mut self.assets {
+            if let Some(target) = asset.payout_target_mut() {
+                if *target == Some(uuid) {
+                    *target = None;
+                }
+            }
+        }
+    }
+
+    /// Adds a copy of the asset right after it, named as a copy. The name is saved with the
+    /// portfolio, so it is not translated.
+    pub fn duplicate_asset(&mut self, uuid: uuid::Uuid) {
+        let Some(index) = self.assets.iter().position(|asset| asset.uuid() == uuid) else {
+            return;
+        };
+        let mut copy = self.assets[index].duplicate();
+        let name = copy.name_mut();
+        *name = format!("{name} (copy)");
+        self.assets.insert(index + 1, copy);
+    }
+
+    /// Moves an asset to where `target` is, and into its group. Moving down places it after
+    /// `target`, moving up before it.
+    pub fn move_asset(&mut self, uuid: uuid::Uuid, target: uuid::Uuid) {
+        let position =
+            |assets: &[Asset], uuid| assets.iter().position(|asset| asset.uuid() == uuid);
+        let (Some(from), Some(to)) = (position(&self.assets, uuid), position(&self.assets, target))
+        else {
+            return;
+        };
+        let group = self.assets[to].meta().group.clone();
+        let mut asset = self.assets.remove(from);
+        asset.meta_mut().group = group;
+        self.assets.insert(to, asset);
+    }
+
+    /// Value of an asset on the given date, including payouts it has received from other assets.
+    pub fn asset_value(&self, asset: &Asset, date: NaiveDate) -> f32 {
+        let received: f32 = self
+            .assets
+            .iter()
+            .filter(|other| other.payout_target() == Some(asset.uuid()))
+            .flat_map(|other| other.payouts(date))
+            .map(|(_, amount)| amount)
+            .sum();
+        asset.value(date) + received
+    }
+
+    /// The asset's contribution to net worth on the given date: negative for debts.
+    pub fn signed_asset_value(&self, asset: &Asset, date: NaiveDate) -> f32 {
+        let value = self.asset_value(asset, date);
+        if asset.is_growth() {
+            value
+        } else {
+            -value
+        }
     }
 
     pub fn total_value(&self, date: NaiveDate) -> f32 {
         self.assets
             .iter()
-            .map(|asset| {
-                let value = asset.value(date);
-                if asset.is_growth() {
-                    value
-                } else {
-                    -value
-                }
-            })
+            .map(|asset| self.signed_asset_value(asset, date))
             .sum()
     }
 
-    pub fn max_value(&self, start_date: NaiveDate, end_date: NaiveDate, interval_days: i64) -> f32 {
-        // find maximum value the portfolio has as as the sum of all assets for given points
-        let mut values: Vec<(NaiveDate, f32)> = Vec::new();
+    /// The assets' groups in the order they first appear, without the empty group.
+    pub fn groups(&self) -> Vec<String> {
+        let mut groups: Vec<String> = Vec::new();
         for asset in &self.assets {
-            let points: Vec<(NaiveDate, f32)> =
-                get_value_points_for_asset(&asset, start_date, end_date, interval_days);
-            // map points to values
-            for (index, value) in points.iter().enumerate() {
-                if values.len() <= index {
-                    values.push((value.0, value.1));
-                } else {
-                    values[index].1 += value.1;
-                }
-            }
-        }
-        let mut max = 0.0;
-        for value in values {
-            if value.1 > max {
-                max = value.1;
+            let group = &asset.meta().group;
+            if !group.is_empty() && !groups.iter().any(|known| known == group) {
+                groups.push(group.to_owned());
             }
         }
-        max
+        groups
     }
 
-    pub fn min_value(&self, start_date: NaiveDate, end_date: NaiveDate, interval_days: i64) -> f32 {
-        // find minimum value the portfolio has as as the sum of all assets for given points
-        let mut values: Vec<(NaiveDate, f32)> = Vec::new();
-        for asset in &self.assets {
-            let points: Vec<(NaiveDate, f32)> =
-                get_value_points_for_asset(&asset, start_date, end_date, interval_days);
-            // map points to values
-            for (index, value) in points.iter().enumerate() {
-                if values.len() <= index {
-                    values.push((value.0, value.1));
-                } else {
-                    values[index].1 += value.1;
-                }
-            }
-        }
-        let mut min = f32::MAX;
-        for value in values {
-            if value.1 < min {
-                min = value.1;
-            }
+    /// All tags used by the assets, sorted.
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .assets
+            .iter()
+            .flat_map(|asset| asset.meta().tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Earliest date any asset has recorded history for.
+    pub fn history_start(&self) -> Option<NaiveDate> {
+        self.assets
+            .iter()
+            .filter_map(|asset| asset.history_start())
+            .min()
+    }
+
+    /// Whether a goal is reached on `date`, or on track to be reached by its target date.
+    pub fn goal_status(&self, goal: &Goal, date: NaiveDate) -> GoalStatus {
+        goal.status(
+            self.total_value(date),
+            self.total_value(goal.target_date.max(date)),
+        )
+    }
+
+    /// Fees charged by all assets from their acquisition up to the given date.
+    pub fn total_fees(&self, date: NaiveDate) -> f32 {
+        self.assets.iter().map(|asset| asset.fees_paid(date)).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bond, Cash};
+
+    #[test]
+    fn clears_payout_targets_of_a_deleted_asset() {
+        let mut portfolio = Portfolio::new();
+        let cash = Cash::default();
+        let other_cash = Cash::default();
+        let (cash_uuid, other_uuid) = (cash.uuid, other_cash.uuid);
+        portfolio.add_asset(Asset::Cash(cash));
+        portfolio.add_asset(Asset::Cash(other_cash));
+        for target in [cash_uuid, other_uuid] {
+            portfolio.add_asset(Asset::Bond(Bond {
+                payout_target: Some(target),
+                ..Bond::default()
+            }));
         }
-        min
+
+        portfolio.delete_asset(cash_uuid);
+        let targets: Vec<_> = portfolio
+            .assets
+            .iter()
+            .filter_map(|asset| match asset {
+                Asset::Bond(bond) => Some(bond.payout_target),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(targets, vec![None, Some(other_uuid)]);
     }
 }
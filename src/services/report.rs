@@ -0,0 +1,251 @@
+use chrono::{Months, NaiveDate};
+
+use crate::{
+    asset::{AssetTrait, AssetType},
+    chart_image::{escape_xml, series_to_svg},
+    export::plot_series,
+    format::{format_amount, format_number},
+    i18n::tr,
+    Asset, Portfolio,
+};
+
+const STYLE: &str =
+    "body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5em; }
+th, td { padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; text-align: left; }
+td.amount, th.amount { text-align: right; font-variant-numeric: tabular-nums; }
+.positive { color: #2a7d2a; } .negative { color: #b03030; }
+svg { width: 100%; height: auto; }
+@media print { body { margin: 0; max-width: none; } section { page-break-inside: avoid; } }";
+
+/// Settings for an annual report.
+pub struct ReportOptions<'a> {
+    pub title: &'a str,
+    pub currency: &'a str,
+    pub date: NaiveDate,     // the day the report is made for
+    pub end_date: NaiveDate, // end of the projection chart
+    pub interval_days: i64,
+}
+
+/// A self-contained HTML report of the portfolio on a given date: net worth by asset and
+/// type, the change over the past year, a projection chart, loan amortization and goal status.
+/// It can be printed to PDF from any browser.
+pub fn annual_report(portfolio: &Portfolio, options: &ReportOptions<'_>) -> String {
+    let date = options.date;
+    let year_ago = date - Months::new(12);
+    let amount = |value: f32| escape_xml(&format_amount(value, options.currency));
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0} {1}</title>\n<style>\n{2}\n</style>\n</head>\n<body>\n<h1>{0}</h1>\n<p>{3} {1}</p>\n",
+        escape_xml(options.title),
+        date,
+        STYLE,
+        tr("Annual report for")
+    );
+
+    // Net worth and its change over the year.
+    let net_worth = portfolio.total_value(date);
+    let last_year = portfolio.total_value(year_ago);
+    html.push_str(&format!(
+        "<section>\n<h2>{}</h2>\n<table>\n",
+        tr("Net Worth")
+    ));
+    html.push_str(&format!(
+        "<tr><td>{} {}</td><td class=\"amount\">{}</td></tr>\n",
+        tr("Net worth on"),
+        date,
+        amount(net_worth)
+    ));
+    html.push_str(&format!(
+        "<tr><td>{} {}</td><td class=\"amount\">{}</td></tr>\n",
+        tr("Net worth on"),
+        year_ago,
+        amount(last_year)
+    ));
+    html.push_str(&format!(
+        "<tr><th>{}</th><th class=\"amount\">{}{}</th></tr>\n",
+        tr("Change since last year"),
+        change_cell(net_worth - last_year, options.currency),
+        percent_change(last_year, net_worth)
+    ));
+    html.push_str(&format!(
+        "<tr><td>{}</td><td class=\"amount\">{}</td></tr>\n",
+        tr("Fees paid during the year"),
+        amount(portfolio.total_fees(date) - portfolio.total_fees(year_ago))
+    ));
+    html.push_str("</table>\n</section>\n");
+
+    // Breakdown by asset.
+    html.push_str(&format!(
+        "<section>\n<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th></tr>\n",
+        tr("Assets"),
+        tr("Asset"),
+        tr("Type"),
+        tr("Last year"),
+        tr("Value"),
+        tr("Change")
+    ));
+    for asset in &portfolio.assets {
+        let value = portfolio.signed_asset_value(asset, date);
+        let previous = portfolio.signed_asset_value(asset, year_ago);
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            escape_xml(&asset.name()),
+            tr(type_name(&asset.asset_type())),
+            amount(previous),
+            amount(value),
+            change_cell(value - previous, options.currency)
+        ));
+    }
+    html.push_str("</table>\n</section>\n");
+
+    // Breakdown by asset type, in the order types first appear.
+    let mut by_type: Vec<(AssetType, f32, f32)> = Vec::new();
+    for asset in &portfolio.assets {
+        let value = portfolio.signed_asset_value(asset, date);
+        let previous = portfolio.signed_asset_value(asset, year_ago);
+        match by_type
+            .iter_mut()
+            .find(|(asset_type, _, _)| *asset_type == asset.asset_type())
+        {
+            Some((_, type_value, type_previous)) => {
+                *type_value += value;
+                *type_previous += previous;
+            }
+            None => by_type.push((asset.asset_type(), value, previous)),
+        }
+    }
+    html.push_str(&format!(
+        "<section>\n<h2>{}</h2>\n<table>\n<tr><th>{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th></tr>\n",
+        tr("By Type"),
+        tr("Type"),
+        tr("Last year"),
+        tr("Value"),
+        tr("Share of net worth")
+    ));
+    for (asset_type, value, previous) in &by_type {
+        let share = if net_worth != 0.0 {
+            format!("{} %", format_number((value / net_worth * 100.0) as f64, 1))
+        } else {
+            "-".to_owned()
+        };
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            tr(type_name(asset_type)),
+            amount(*previous),
+            amount(*value),
+            share
+        ));
+    }
+    html.push_str("</table>\n</section>\n");
+
+    // Projection chart from the report date.
+    let series = plot_series(portfolio, date, options.end_date, options.interval_days);
+    html.push_str(&format!("<section>\n<h2>{}</h2>\n", tr("Projection")));
+    html.push_str(&series_to_svg(&series, options.title, options.currency));
+    html.push_str("</section>\n");
+
+    // Loan amortization over the year.
+    let loans: Vec<_> = portfolio
+        .assets
+        .iter()
+        .filter_map(|asset| match asset {
+            Asset::Loan(loan) => Some(loan),
+            _ => None,
+        })
+        .collect();
+    if !loans.is_empty() {
+        html.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n<table>\n<tr><th>{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th><th class=\"amount\">{}</th><th>{}</th></tr>\n",
+            tr("Loans"),
+            tr("Loan"),
+            tr("Balance last year"),
+            tr("Balance"),
+            tr("Principal paid"),
+            tr("Interest paid"),
+            tr("Paid off")
+        ));
+        for loan in loans {
+            let payoff = loan
+                .payoff_date()
+                .map_or(tr("Not within 100 years").to_owned(), |payoff| {
+                    payoff.to_string()
+                });
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td>{}</td></tr>\n",
+                escape_xml(&loan.name),
+                amount(loan.value(year_ago)),
+                amount(loan.value(date)),
+                amount(loan.principal_paid(date) - loan.principal_paid(year_ago)),
+                amount(loan.interest_between(year_ago, date)),
+                payoff
+            ));
+        }
+        html.push_str("</table>\n</section>\n");
+    }
+
+    // Goals.
+    if !portfolio.goals.is_empty() {
+        html.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n<table>\n<tr><th>{}</th><th class=\"amount\">{}</th><th>{}</th><th class=\"amount\">{}</th><th>{}</th></tr>\n",
+            tr("Goals"),
+            tr("Goal"),
+            tr("Target"),
+            tr("By"),
+            tr("Projected"),
+            tr("Status")
+        ));
+        for goal in &portfolio.goals {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"amount\">{}</td><td>{}</td><td class=\"amount\">{}</td><td>{}</td></tr>\n",
+                escape_xml(&goal.name),
+                amount(goal.target_value),
+                goal.target_date,
+                amount(portfolio.total_value(goal.target_date.max(date))),
+                tr(portfolio.goal_status(goal, date).label())
+            ));
+        }
+        html.push_str("</table>\n</section>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn type_name(asset_type: &AssetType) -> &'static str {
+    match asset_type {
+        AssetType::RealEstate => "Real Estate",
+        AssetType::Loan => "Loan",
+        AssetType::Tradable => "Tradable",
+        AssetType::Cash => "Cash",
+        AssetType::Depreciating => "Depreciating",
+        AssetType::Bond => "Bond",
+        AssetType::CreditCard => "Credit Card",
+        AssetType::EquityGrant => "Equity Grant",
+    }
+}
+
+fn change_cell(change: f32, currency: &str) -> String {
+    let class = if change >= 0.0 {
+        "positive"
+    } else {
+        "negative"
+    };
+    let sign = if change > 0.0 { "+" } else { "" };
+    format!(
+        "<span class=\"{}\">{}{}</span>",
+        class,
+        sign,
+        escape_xml(&format_amount(change, currency))
+    )
+}
+
+fn percent_change(previous: f32, current: f32) -> String {
+    if previous > 0.0 {
+        let change = (current - previous) / previous * 100.0;
+        let sign = if change > 0.0 { "+" } else { "" };
+        format!(" ({}{} %)", sign, format_number(change as f64, 1))
+    } else {
+        String::new()
+    }
+}
@@ -0,0 +1,140 @@
+use std::f32::consts::TAU;
+
+use chrono::NaiveDate;
+use egui::{Color32, Pos2, Shape, Stroke, Ui};
+use egui_plot::{Bar, BarChart};
+
+use crate::{
+    asset::AssetTrait,
+    format::{format_amount, format_number},
+    i18n::tr,
+    Portfolio,
+};
+
+/// Largest angle of one pie wedge segment. Wedges are drawn as convex polygons, so larger
+/// slices are split into several segments.
+const MAX_SEGMENT_ANGLE: f32 = TAU / 16.0;
+
+/// Each asset's name, color and contribution to net worth on the given date.
+fn allocation(portfolio: &Portfolio, date: NaiveDate) -> Vec<(String, Color32, f32)> {
+    portfolio
+        .assets
+        .iter()
+        .map(|asset| {
+            (
+                asset.name(),
+                asset.color(),
+                portfolio.signed_asset_value(asset, date),
+            )
+        })
+        .collect()
+}
+
+/// Pie chart of how the assets are allocated on the given date, with a legend listing each
+/// share. Liabilities are not part of the pie and are listed below it. Returns the response
+/// covering both the pie and the legend.
+pub fn allocation_pie(
+    ui: &mut Ui,
+    portfolio: &Portfolio,
+    date: NaiveDate,
+    currency: &str,
+) -> egui::Response {
+    let slices = allocation(portfolio, date);
+    let assets_total: f32 = slices.iter().map(|(_, _, value)| value.max(0.0)).sum();
+    let liabilities_total: f32 = slices.iter().map(|(_, _, value)| value.min(0.0)).sum();
+
+    ui.horizontal(|ui| {
+        let size = ui
+            .available_height()
+            .min(ui.available_width() * 0.6)
+            .max(100.0);
+        let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+        let center = response.rect.center();
+        let radius = size / 2.0 - 8.0;
+
+        if assets_total > 0.0 {
+            let mut start_angle = -TAU / 4.0;
+            for (_, color, value) in slices.iter().filter(|(_, _, value)| *value > 0.0) {
+                let angle = value / assets_total * TAU;
+                let segments = (angle / MAX_SEGMENT_ANGLE).ceil().max(1.0) as usize;
+                for segment in 0..segments {
+                    let from = start_angle + angle * segment as f32 / segments as f32;
+                    let to = start_angle + angle * (segment + 1) as f32 / segments as f32;
+                    let points: Vec<Pos2> = [from, (from + to) / 2.0, to]
+                        .into_iter()
+                        .map(|angle| center + radius * egui::vec2(angle.cos(), angle.sin()))
+                        .collect();
+                    let mut wedge = vec![center];
+                    wedge.extend(points);
+                    painter.add(Shape::convex_polygon(wedge, *color, Stroke::NONE));
+                }
+                start_angle += angle;
+            }
+        } else {
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                tr("No assets"),
+                egui::FontId::default(),
+                ui.visuals().text_color(),
+            );
+        }
+
+        ui.vertical(|ui| {
+            egui::Grid::new("allocation_legend")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (name, color, value) in &slices {
+                        let share = if *value > 0.0 && assets_total > 0.0 {
+                            format!(
+                                "{} %",
+                                format_number((value / assets_total * 100.0) as f64, 1)
+                            )
+                        } else {
+                            String::new()
+                        };
+                        let (swatch, _) =
+                            ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                        ui.painter().rect_filled(swatch, 2.0, *color);
+                        ui.label(name);
+                        ui.label(format_amount(*value, currency));
+                        ui.label(share);
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            ui.label(format!(
+                "{}{}",
+                tr("Assets: "),
+                format_amount(assets_total, currency)
+            ));
+            ui.label(format!(
+                "{}{}",
+                tr("Liabilities: "),
+                format_amount(liabilities_total, currency)
+            ));
+            ui.strong(format!(
+                "{}{}",
+                tr("Net worth: "),
+                format_amount(assets_total + liabilities_total, currency)
+            ));
+        });
+    })
+    .response
+}
+
+/// One bar per asset with its contribution to net worth on the given date, liabilities below
+/// zero. Each asset is its own chart so that it gets a legend entry in its color.
+pub fn allocation_bar_charts(portfolio: &Portfolio, date: NaiveDate) -> Vec<BarChart> {
+    allocation(portfolio, date)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, color, value))| {
+            BarChart::new(vec![Bar::new(index as f64, value as f64)
+                .name(&name)
+                .width(0.7)])
+            .name(name)
+            .color(color)
+        })
+        .collect()
+}
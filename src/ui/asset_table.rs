@@ -0,0 +1,242 @@
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    asset::{AssetTrait, AssetType},
+    format::{format_amount, format_number},
+    i18n::tr,
+    widgets::{amount_drag_value, number_drag_value},
+    Portfolio,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum AssetColumn {
+    Name,
+    Type,
+    Value,    // today
+    EndValue, // at the end of the plot
+    Rate,
+    MonthlyContribution,
+    Share, // of today's net worth
+}
+
+impl AssetColumn {
+    pub const ALL: [AssetColumn; 7] = [
+        AssetColumn::Name,
+        AssetColumn::Type,
+        AssetColumn::Value,
+        AssetColumn::EndValue,
+        AssetColumn::Rate,
+        AssetColumn::MonthlyContribution,
+        AssetColumn::Share,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetColumn::Name => "Name",
+            AssetColumn::Type => "Type",
+            AssetColumn::Value => "Value",
+            AssetColumn::EndValue => "Value at End",
+            AssetColumn::Rate => "Rate (%)",
+            AssetColumn::MonthlyContribution => "Per Month",
+            AssetColumn::Share => "Share",
+        }
+    }
+}
+
+/// The assets as a table with one row per asset. Clicking a column header sorts by that
+/// column and clicking it again reverses the order. Unsorted, the rows follow the portfolio.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct AssetTable {
+    pub sort_column: Option<AssetColumn>,
+    pub descending: bool,
+    #[serde(skip)]
+    order: Vec<Uuid>, // rows as last shown, kept while a cell is edited
+}
+
+/// What a row shows and is sorted by.
+struct Row {
+    index: usize, // into `Portfolio::assets`
+    uuid: Uuid,
+    name: String,
+    tags: Vec<String>,
+    asset_type: AssetType,
+    value: f32,
+    end_value: f32,
+    rate: f32,
+    rate_used: bool,      // false if the asset's model ignores the rate
+    rate_scheduled: bool, // a rate schedule sets the rate
+    monthly_contribution: Option<f32>,
+    contribution_planned: bool, // a contribution plan raises, pauses or ends the contribution
+    share: f32,
+}
+
+impl AssetTable {
+    /// Shows the table with the name, rate and monthly contribution editable in place. With a
+    /// tag, only the assets with that tag are shown. The rate and contribution show the values
+    /// in effect on `date`, and are only editable while no schedule or plan changes them.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        portfolio: &mut Portfolio,
+        date: NaiveDate,
+        end_date: NaiveDate,
+        currency: &str,
+        tag: Option<&str>,
+    ) {
+        let mut rows = rows(portfolio, date, end_date);
+        rows.retain(|row| tag.map_or(true, |tag| row.tags.iter().any(|row_tag| row_tag == tag)));
+
+        egui::Grid::new("asset_table")
+            .striped(true)
+            .num_columns(AssetColumn::ALL.len())
+            .show(ui, |ui| {
+                for column in AssetColumn::ALL {
+                    let sorted = self.sort_column == Some(column);
+                    let arrow = match (sorted, self.descending) {
+                        (false, _) => "",
+                        (true, false) => egui_material_icons::icons::ICON_ARROW_UPWARD,
+                        (true, true) => egui_material_icons::icons::ICON_ARROW_DOWNWARD,
+                    };
+                    let header = format!("{} {}", tr(column.label()), arrow);
+                    if ui.selectable_label(sorted, header).clicked() {
+                        self.descending = sorted && !self.descending;
+                        self.sort_column = Some(column);
+                    }
+                }
+                ui.end_row();
+
+                // While a cell is edited the rows keep their order, so that the edited row
+                // does not move away from under the pointer or the keyboard focus.
+                let editing = ui.ctx().memory(|memory| memory.focused().is_some())
+                    || ui.ctx().dragged_id().is_some();
+                let same_rows = rows.len() == self.order.len()
+                    && rows.iter().all(|row| self.order.contains(&row.uuid));
+                if editing && same_rows {
+                    rows.sort_by_key(|row| self.order.iter().position(|uuid| *uuid == row.uuid));
+                } else {
+                    self.sort(&mut rows);
+                    self.order = rows.iter().map(|row| row.uuid).collect();
+                }
+
+                for row in &rows {
+                    let asset = &mut portfolio.assets[row.index];
+                    let color = asset.color();
+                    ui.add(
+                        egui::TextEdit::singleline(asset.name_mut())
+                            .id_salt(("asset_table_name", row.uuid))
+                            .text_color(color)
+                            .desired_width(120.0),
+                    );
+                    ui.label(tr(&format!("{:?}", row.asset_type)));
+                    ui.label(format_amount(row.value, currency));
+                    ui.label(format_amount(row.end_value, currency));
+                    if !row.rate_used {
+                        ui.label("-")
+                            .on_hover_text(tr("Not used by the depreciation model"));
+                    } else if row.rate_scheduled {
+                        ui.label(format_number(row.rate as f64, 1))
+                            .on_hover_text(tr("Set by the rate schedule"));
+                    } else {
+                        let range = asset.rate_range();
+                        ui.push_id(("asset_table_rate", row.uuid), |ui| {
+                            ui.add(number_drag_value(asset.rate_mut()).speed(0.1).range(range))
+                        });
+                    }
+                    match (asset.contribution_mut(), row.monthly_contribution) {
+                        (Some((contribution, frequency)), Some(monthly))
+                            if !row.contribution_planned =>
+                        {
+                            let mut monthly = monthly;
+                            let changed = ui
+                                .push_id(("asset_table_contribution", row.uuid), |ui| {
+                                    ui.add(amount_drag_value(&mut monthly, currency).speed(10.0))
+                                })
+                                .inner
+                                .changed();
+                            if changed {
+                                *contribution = monthly / frequency.per_month();
+                            }
+                        }
+                        (_, Some(monthly)) => {
+                            ui.label(format_amount(monthly, currency))
+                                .on_hover_text(tr("Set by the contribution plan"));
+                        }
+                        (_, None) => {
+                            ui.label("-");
+                        }
+                    }
+                    ui.label(format!("{} %", format_number(row.share as f64 * 100.0, 1)));
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn sort(&self, rows: &mut [Row]) {
+        let Some(column) = self.sort_column else {
+            return;
+        };
+        rows.sort_by(|a, b| {
+            let ordering = match column {
+                AssetColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                AssetColumn::Type => {
+                    tr(&format!("{:?}", a.asset_type)).cmp(tr(&format!("{:?}", b.asset_type)))
+                }
+                AssetColumn::Value => a.value.total_cmp(&b.value),
+                AssetColumn::EndValue => a.end_value.total_cmp(&b.end_value),
+                AssetColumn::Rate => a.rate.total_cmp(&b.rate),
+                AssetColumn::MonthlyContribution => {
+                    match (a.monthly_contribution, b.monthly_contribution) {
+                        (Some(a), Some(b)) => a.total_cmp(&b),
+                        (Some(_), None) => Ordering::Greater,
+                        (None, Some(_)) => Ordering::Less,
+                        (None, None) => Ordering::Equal,
+                    }
+                }
+                AssetColumn::Share => a.share.total_cmp(&b.share),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
+/// One row per asset, in portfolio order.
+fn rows(portfolio: &Portfolio, date: NaiveDate, end_date: NaiveDate) -> Vec<Row> {
+    let net_worth = portfolio.total_value(date);
+    portfolio
+        .assets
+        .iter()
+        .enumerate()
+        .map(|(index, asset)| {
+            let value = portfolio.signed_asset_value(asset, date);
+            Row {
+                index,
+                uuid: asset.uuid(),
+                name: asset.name(),
+                tags: asset.meta().tags.clone(),
+                asset_type: asset.asset_type(),
+                value,
+                end_value: portfolio.signed_asset_value(asset, end_date),
+                rate: asset.rate_at(date),
+                rate_used: asset.rate_used(),
+                rate_scheduled: asset.rate_scheduled_at(date),
+                monthly_contribution: asset
+                    .contribution_at(date)
+                    .map(|(contribution, frequency)| contribution * frequency.per_month()),
+                contribution_planned: asset.contribution_planned_at(date),
+                share: if net_worth != 0.0 {
+                    value / net_worth
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,231 @@
+use uuid::Uuid;
+
+use crate::{
+    asset::AssetTrait,
+    csv::{CsvColumn, CsvImport},
+    i18n::tr,
+    widgets::amount_drag_value,
+    Asset, Portfolio,
+};
+
+/// Number of data rows shown in the preview.
+const PREVIEW_ROWS: usize = 10;
+
+/// Window for pasting or dropping a CSV file, mapping its columns and importing it into an asset.
+#[derive(Default)]
+pub struct CsvImportDialog {
+    pub open: bool,
+    text: String,
+    import: CsvImport,
+    target: Option<Uuid>,
+    ticker: Option<String>,
+    opening_balance: f32,
+    status: Option<String>,
+}
+
+impl CsvImportDialog {
+    pub fn show(&mut self, ctx: &egui::Context, portfolio: &mut Portfolio, currency: &str) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(tr("Import CSV"))
+            .id(Self::layer_id().id)
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.label(tr("Paste CSV text below or drop a file onto the window."));
+                let text_changed = ui
+                    .add(
+                        egui::TextEdit::multiline(&mut self.text)
+                            .code_editor()
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed();
+                if text_changed {
+                    self.import = CsvImport::new(&self.text);
+                    self.ticker = None;
+                    self.status = None;
+                }
+
+                if self.import.rows.is_empty() {
+                    return;
+                }
+                ui.separator();
+                self.mapping_ui(ui);
+                ui.separator();
+                self.target_ui(ui, portfolio, currency);
+                ui.separator();
+
+                if ui.button(tr("Import")).clicked() {
+                    self.status = Some(self.apply(portfolio));
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        self.open = open;
+    }
+
+    /// The layer of the dialog's window, to tell whether it is in front.
+    pub fn layer_id() -> egui::LayerId {
+        egui::LayerId::new(egui::Order::Middle, egui::Id::new("csv_import_dialog"))
+    }
+
+    /// Replaces the CSV text, e.g. with a dropped file, and detects its columns anew.
+    pub fn set_text(&mut self, text: String) {
+        self.import = CsvImport::new(&text);
+        self.text = text;
+        self.ticker = None;
+        self.status = None;
+    }
+
+    /// Column type selectors above a preview of the first rows.
+    fn mapping_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.import.has_header, tr("First row is a header"));
+
+        let column_count = self.import.rows.iter().map(Vec::len).max().unwrap_or(0);
+        self.import.columns.resize(column_count, CsvColumn::Ignore);
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            egui::Grid::new("csv_preview").striped(true).show(ui, |ui| {
+                for (index, column) in self.import.columns.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_salt(("csv_column", index))
+                        .selected_text(tr(&format!("{:?}", column)))
+                        .show_ui(ui, |ui| {
+                            for option in CsvColumn::ALL {
+                                ui.selectable_value(column, option, tr(&format!("{:?}", option)));
+                            }
+                        });
+                }
+                ui.end_row();
+
+                if let Some(header) = self.import.header() {
+                    for field in header {
+                        ui.strong(field);
+                    }
+                    ui.end_row();
+                }
+                for row in self.import.data_rows().iter().take(PREVIEW_ROWS) {
+                    for field in row {
+                        ui.label(field);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        ui.label(format!(
+            "{} {}, {} {}",
+            self.import.data_rows().len(),
+            tr("rows"),
+            self.import.records().len(),
+            tr("with a valid date")
+        ));
+    }
+
+    /// Selects the asset to import into, with options depending on its type.
+    fn target_ui(&mut self, ui: &mut egui::Ui, portfolio: &Portfolio, currency: &str) {
+        let targets: Vec<(Uuid, String)> = portfolio
+            .assets
+            .iter()
+            .filter(|asset| {
+                matches!(
+                    asset,
+                    Asset::Cash(_)
+                        | Asset::Tradable(crate::Tradable {
+                            holding: Some(_),
+                            ..
+                        })
+                )
+            })
+            .map(|asset| (asset.uuid(), asset.name()))
+            .collect();
+        let selected_text = targets
+            .iter()
+            .find(|(uuid, _)| Some(*uuid) == self.target)
+            .map_or(tr("Select asset").to_owned(), |(_, name)| name.clone());
+
+        let previous_target = self.target;
+        ui.horizontal(|ui| {
+            ui.label(tr("Import into: "));
+            egui::ComboBox::from_id_salt("csv_import_target")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (uuid, name) in &targets {
+                        ui.selectable_value(&mut self.target, Some(*uuid), name);
+                    }
+                });
+        });
+        if self.target != previous_target {
+            self.ticker = None;
+        }
+
+        let target = portfolio
+            .assets
+            .iter()
+            .find(|asset| Some(asset.uuid()) == self.target);
+        match target {
+            Some(Asset::Cash(_)) if !self.import.has_column(CsvColumn::Balance) => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Opening Balance: "));
+                    ui.add(amount_drag_value(&mut self.opening_balance, currency).speed(100.0));
+                });
+            }
+            Some(Asset::Tradable(tradable)) if self.import.has_column(CsvColumn::Ticker) => {
+                // Rows of different tickers cannot share one price history, so one ticker is
+                // imported: the holding's own if the file has it.
+                let tickers = self.import.tickers();
+                if self.ticker.is_none() {
+                    let own_ticker = tradable
+                        .holding
+                        .as_ref()
+                        .map(|holding| &holding.ticker)
+                        .filter(|ticker| tickers.contains(ticker));
+                    self.ticker = match (own_ticker, tickers.as_slice()) {
+                        (Some(ticker), _) | (None, [ticker]) => Some(ticker.clone()),
+                        _ => None,
+                    };
+                }
+                ui.horizontal(|ui| {
+                    ui.label(tr("Ticker: "));
+                    egui::ComboBox::from_id_salt("csv_import_ticker")
+                        .selected_text(
+                            self.ticker
+                                .clone()
+                                .unwrap_or(tr("Select ticker").to_owned()),
+                        )
+                        .show_ui(ui, |ui| {
+                            for ticker in tickers {
+                                ui.selectable_value(&mut self.ticker, Some(ticker.clone()), ticker);
+                            }
+                        });
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Imports into the selected asset and returns a translated status message.
+    fn apply(&self, portfolio: &mut Portfolio) -> String {
+        let Some(asset) = portfolio
+            .assets
+            .iter_mut()
+            .find(|asset| Some(asset.uuid()) == self.target)
+        else {
+            return tr("Select an asset to import into").to_owned();
+        };
+        let ticker = self
+            .ticker
+            .as_deref()
+            .filter(|_| self.import.has_column(CsvColumn::Ticker));
+        match self
+            .import
+            .apply_to_asset(asset, ticker, self.opening_balance)
+        {
+            Ok(imported) => format!("{} {}: {imported}", tr("Rows imported into"), asset.name()),
+            Err(error) => tr(&error).to_owned(),
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod allocation;
+pub mod asset_table;
+pub mod csv_import_dialog;
 pub mod plot_utils;
+pub mod statement_import_dialog;
+pub mod widgets;
@@ -1,12 +1,17 @@
 // // src/plot_utils.rs
 
-use chrono::{Duration, NaiveDate};
-use egui_plot::{Line, PlotPoints};
+use std::ops::RangeInclusive;
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use egui_plot::{Line, PlotBounds, PlotGeometry, PlotItem, PlotPoint, PlotPoints, PlotTransform};
 use uuid::Uuid;
 
-use crate::{asset::AssetTrait, Asset, Portfolio};
+use crate::{
+    asset::AssetTrait, export::PlotSeries, format::format_amount, i18n::tr, Asset, Portfolio,
+};
 
 pub fn get_value_points_for_asset(
+    portfolio: &Portfolio,
     asset: &Asset,
     start_date: NaiveDate,
     end_date: NaiveDate,
@@ -16,7 +21,7 @@ pub fn get_value_points_for_asset(
     let mut data_points: Vec<(NaiveDate, f32)> = Vec::new();
 
     while current_date <= end_date {
-        let value = asset.value(current_date);
+        let value = portfolio.asset_value(asset, current_date);
         data_points.push((current_date, value));
         current_date += Duration::days(interval_days);
     }
@@ -24,28 +29,34 @@ pub fn get_value_points_for_asset(
     data_points
 }
 
-pub fn create_plot_line(
-    asset: Asset,
+/// Dashed line of the asset's unvested value, or `None` if nothing is ever unvested.
+pub fn create_unvested_plot_line(
+    asset: &Asset,
     start_date: NaiveDate,
     end_date: NaiveDate,
     interval_days: i64,
-) -> Line<'static> {
-    let data_points = get_value_points_for_asset(&asset, start_date, end_date, interval_days);
-
-    let plot_points: Vec<[f64; 2]> = data_points
-        .into_iter()
-        .map(|(date, value)| {
-            // Convert the date into a UTC timestamp (at midnight) and then to f64.
-            let timestamp = date
-                .and_hms_opt(0, 0, 0)
-                .expect("Invalid time")
-                .and_utc()
-                .timestamp() as f64;
-            [timestamp, value as f64]
-        })
-        .collect();
+) -> Option<Line<'static>> {
+    let mut plot_points: Vec<[f64; 2]> = Vec::new();
+    let mut current_date = start_date;
+    while current_date <= end_date {
+        let timestamp = current_date
+            .and_hms_opt(0, 0, 0)
+            .expect("Invalid time")
+            .and_utc()
+            .timestamp() as f64;
+        plot_points.push([timestamp, asset.unvested_value(current_date) as f64]);
+        current_date += Duration::days(interval_days);
+    }
 
-    Line::new(PlotPoints::new(plot_points))
+    if plot_points.iter().all(|point| point[1] == 0.0) {
+        return None;
+    }
+    Some(
+        Line::new(PlotPoints::new(plot_points))
+            .name(format!("{} ({})", asset.name(), tr("unvested")))
+            .color(asset.color())
+            .style(egui_plot::LineStyle::dashed_loose()),
+    )
 }
 
 pub fn get_random_bytes_from_uuid(uuid: &Uuid) -> u8 {
@@ -72,27 +83,226 @@ pub fn get_portfolio_value_points(
     data_points
 }
 
-pub fn create_portfolio_plot_line(
-    portfolio: &Portfolio,
-    start_date: NaiveDate,
-    end_date: NaiveDate,
-    interval_days: i64,
-) -> Line<'_> {
-    let data_points = get_portfolio_value_points(portfolio, start_date, end_date, interval_days);
-    let plot_points: Vec<[f64; 2]> = data_points
-        .into_iter()
+/// Line drawn from an already computed series, with its name and color.
+pub fn create_series_plot_line(series: &PlotSeries) -> Line<'static> {
+    let plot_points: Vec<[f64; 2]> = series
+        .points
+        .iter()
         .map(|(date, value)| {
-            // Convert the date (at midnight) to a timestamp.
             let timestamp = date
                 .and_hms_opt(0, 0, 0)
                 .expect("Invalid time")
                 .and_utc()
                 .timestamp() as f64;
-            [timestamp, value as f64]
+            [timestamp, *value as f64]
         })
         .collect();
     Line::new(PlotPoints::new(plot_points))
-        .name("Portfolio Total")
-        .color(egui::Color32::LIGHT_BLUE)
-        .width(2.0)
+        .name(series.display_name())
+        .color(series.color)
+}
+
+/// One asset's band in the stacked area chart: the area between a lower and an upper edge
+/// sampled at the same x coordinates, drawn as a single triangle mesh.
+pub struct StackedBand {
+    name: String,
+    color: egui::Color32,
+    points: Vec<(f64, f64, f64)>, // x, lower edge, upper edge
+    highlighted: bool,
+}
+
+impl PlotItem for StackedBand {
+    fn shapes(&self, _ui: &egui::Ui, transform: &PlotTransform, shapes: &mut Vec<egui::Shape>) {
+        let fill_color = if self.highlighted {
+            self.color
+        } else {
+            self.color.gamma_multiply(0.6)
+        };
+        let mut mesh = egui::Mesh::default();
+        for (x, lower, upper) in &self.points {
+            mesh.colored_vertex(
+                transform.position_from_point(&PlotPoint::new(*x, *lower)),
+                fill_color,
+            );
+            mesh.colored_vertex(
+                transform.position_from_point(&PlotPoint::new(*x, *upper)),
+                fill_color,
+            );
+        }
+        // Two triangles between each pair of neighbouring samples.
+        for index in 1..self.points.len() as u32 {
+            let (previous_lower, previous_upper) = (2 * index - 2, 2 * index - 1);
+            let (lower, upper) = (2 * index, 2 * index + 1);
+            mesh.add_triangle(previous_lower, lower, upper);
+            mesh.add_triangle(previous_lower, upper, previous_upper);
+        }
+        shapes.push(egui::Shape::mesh(mesh));
+    }
+
+    fn initialize(&mut self, _x_range: RangeInclusive<f64>) {}
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn color(&self) -> egui::Color32 {
+        self.color
+    }
+
+    fn highlight(&mut self) {
+        self.highlighted = true;
+    }
+
+    fn highlighted(&self) -> bool {
+        self.highlighted
+    }
+
+    fn allow_hover(&self) -> bool {
+        false
+    }
+
+    fn geometry(&self) -> PlotGeometry<'_> {
+        PlotGeometry::None
+    }
+
+    fn bounds(&self) -> PlotBounds {
+        let mut bounds = PlotBounds::NOTHING;
+        for (x, lower, upper) in &self.points {
+            bounds.extend_with(&PlotPoint::new(*x, *lower));
+            bounds.extend_with(&PlotPoint::new(*x, *upper));
+        }
+        bounds
+    }
+
+    fn id(&self) -> Option<egui::Id> {
+        None
+    }
+}
+
+/// Stacked areas of the assets above zero and the liabilities below it, as one band per asset
+/// and sign. Where an asset changes sign between two dates, every band gets an extra sample at
+/// the crossing, so that the positive and negative stacks meet exactly at zero.
+pub fn create_stacked_area_bands(
+    portfolio: &Portfolio,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    interval_days: i64,
+) -> Vec<StackedBand> {
+    let mut dates = Vec::new();
+    let mut current_date = start_date;
+    while current_date <= end_date {
+        dates.push(current_date);
+        current_date += Duration::days(interval_days);
+    }
+    let timestamps: Vec<f64> = dates
+        .iter()
+        .map(|date| {
+            date.and_hms_opt(0, 0, 0)
+                .expect("Invalid time")
+                .and_utc()
+                .timestamp() as f64
+        })
+        .collect();
+    let values: Vec<Vec<f64>> = portfolio
+        .assets
+        .iter()
+        .map(|asset| {
+            dates
+                .iter()
+                .map(|date| portfolio.signed_asset_value(asset, *date) as f64)
+                .collect()
+        })
+        .collect();
+
+    // Sample positions as (interval index, fraction of the interval): the dates themselves and
+    // every zero crossing of any asset.
+    let mut samples: Vec<(usize, f64)> = (0..dates.len()).map(|index| (index, 0.0)).collect();
+    for asset_values in &values {
+        for (index, pair) in asset_values.windows(2).enumerate() {
+            if pair[0] * pair[1] < 0.0 {
+                samples.push((index, pair[0] / (pair[0] - pair[1])));
+            }
+        }
+    }
+    samples.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    samples.dedup();
+    let interpolate = |series: &[f64], (index, fraction): (usize, f64)| {
+        if fraction == 0.0 {
+            series[index]
+        } else {
+            series[index] + fraction * (series[index + 1] - series[index])
+        }
+    };
+    let xs: Vec<f64> = samples
+        .iter()
+        .map(|sample| interpolate(&timestamps, *sample))
+        .collect();
+
+    // Running top of the positive stack and bottom of the negative stack at each sample.
+    let mut positive_base = vec![0.0f64; samples.len()];
+    let mut negative_base = vec![0.0f64; samples.len()];
+    let mut bands = Vec::new();
+    for (asset, asset_values) in portfolio.assets.iter().zip(&values) {
+        for (base, sign) in [(&mut positive_base, 1.0), (&mut negative_base, -1.0)] {
+            let mut points = Vec::with_capacity(samples.len());
+            for (index, sample) in samples.iter().enumerate() {
+                let value = interpolate(asset_values, *sample);
+                let lower = base[index];
+                if value * sign > 0.0 {
+                    base[index] += value;
+                }
+                points.push((xs[index], lower, base[index]));
+            }
+            if points.iter().any(|(_, lower, upper)| lower != upper) {
+                bands.push(StackedBand {
+                    name: asset.name(),
+                    color: asset.color(),
+                    points,
+                    highlighted: false,
+                });
+            }
+        }
+    }
+    bands
+}
+
+/// Formats a plot x coordinate, a UTC timestamp, as a date.
+pub fn format_timestamp(x: f64) -> String {
+    match Utc.timestamp_opt(x as i64, 0) {
+        chrono::LocalResult::Single(date) => date.format("%Y-%m-%d").to_string(),
+        _ => "Invalid date".to_owned(),
+    }
+}
+
+/// Hover text for the plotted date closest to `x`: the date, each series' value and, last,
+/// the portfolio total.
+pub fn hover_text(series: &[PlotSeries], currency: &str, x: f64) -> String {
+    let Some(index) = series.first().and_then(|line| {
+        line.points
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (date, _))| {
+                let timestamp = date
+                    .and_hms_opt(0, 0, 0)
+                    .expect("Invalid time")
+                    .and_utc()
+                    .timestamp();
+                (timestamp - x as i64).abs()
+            })
+            .map(|(index, _)| index)
+    }) else {
+        return String::new();
+    };
+
+    let mut text = series[0].points[index].0.format("%Y-%m-%d").to_string();
+    for line in series {
+        if let Some((_, value)) = line.points.get(index) {
+            text.push_str(&format!(
+                "\n{}: {}",
+                line.display_name(),
+                format_amount(*value, currency)
+            ));
+        }
+    }
+    text
 }
@@ -0,0 +1,157 @@
+use uuid::Uuid;
+
+use crate::{
+    asset::AssetTrait,
+    format::format_number,
+    i18n::tr,
+    statement::{Statement, StatementFormat},
+    Asset, Portfolio,
+};
+
+/// Window for importing an OFX/QFX or camt.053 bank statement into a cash asset.
+#[derive(Default)]
+pub struct StatementImportDialog {
+    pub open: bool,
+    text: String,
+    statement: Option<Result<Statement, String>>,
+    target: Option<Uuid>,
+    status: Option<String>,
+}
+
+impl StatementImportDialog {
+    pub fn show(&mut self, ctx: &egui::Context, portfolio: &mut Portfolio) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new(tr("Import Bank Statement"))
+            .id(Self::layer_id().id)
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.label(tr(
+                    "Paste an OFX, QFX or camt.053 file below or drop it onto the window.",
+                ));
+                let text_changed = ui
+                    .add(
+                        egui::TextEdit::multiline(&mut self.text)
+                            .code_editor()
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY),
+                    )
+                    .changed();
+                if text_changed {
+                    self.parse();
+                }
+
+                let statement = match &self.statement {
+                    Some(Ok(statement)) => statement,
+                    Some(Err(error)) => {
+                        ui.label(tr(error));
+                        return;
+                    }
+                    None => return,
+                };
+                ui.separator();
+                Self::summary_ui(ui, &self.text, statement);
+                ui.separator();
+
+                let targets: Vec<(Uuid, String)> = portfolio
+                    .assets
+                    .iter()
+                    .filter(|asset| matches!(asset, Asset::Cash(_)))
+                    .map(|asset| (asset.uuid(), asset.name()))
+                    .collect();
+                let selected_text = targets
+                    .iter()
+                    .find(|(uuid, _)| Some(*uuid) == self.target)
+                    .map_or(tr("Select asset").to_owned(), |(_, name)| name.clone());
+                ui.horizontal(|ui| {
+                    ui.label(tr("Import into: "));
+                    egui::ComboBox::from_id_salt("statement_import_target")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (uuid, name) in &targets {
+                                ui.selectable_value(&mut self.target, Some(*uuid), name);
+                            }
+                        });
+                });
+
+                if ui.button(tr("Import")).clicked() {
+                    let cash = portfolio.assets.iter_mut().find_map(|asset| match asset {
+                        Asset::Cash(cash) if Some(cash.uuid) == self.target => Some(cash),
+                        _ => None,
+                    });
+                    self.status = Some(match cash {
+                        Some(cash) => {
+                            let added = cash.import_statement(statement);
+                            let skipped = statement.transactions.len() - added;
+                            format!(
+                                "{} {}: {added}. {} {skipped}",
+                                tr("Transactions imported into"),
+                                cash.name,
+                                tr("Already imported, skipped:"),
+                            )
+                        }
+                        None => tr("Select a cash asset to import into").to_owned(),
+                    });
+                }
+                if let Some(status) = &self.status {
+                    ui.label(status);
+                }
+            });
+        self.open = open;
+    }
+
+    /// The layer of the dialog's window, to tell whether it is in front.
+    pub fn layer_id() -> egui::LayerId {
+        egui::LayerId::new(
+            egui::Order::Middle,
+            egui::Id::new("statement_import_dialog"),
+        )
+    }
+
+    /// Replaces the statement text, e.g. with a dropped file, and parses it.
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.parse();
+    }
+
+    fn parse(&mut self) {
+        self.statement = (!self.text.trim().is_empty()).then(|| Statement::parse(&self.text));
+        self.status = None;
+    }
+
+    fn summary_ui(ui: &mut egui::Ui, text: &str, statement: &Statement) {
+        let format = match Statement::detect_format(text) {
+            Some(StatementFormat::Ofx) => "OFX",
+            Some(StatementFormat::Camt053) => "camt.053",
+            None => tr("Unknown"),
+        };
+        if let (Some(first), Some(last)) = (
+            statement.transactions.first(),
+            statement.transactions.last(),
+        ) {
+            ui.label(format!(
+                "{format}: {} {}, {} – {}",
+                statement.transactions.len(),
+                tr("transactions"),
+                first.date,
+                last.date
+            ));
+        }
+        if let Some(closing_balance) = &statement.closing_balance {
+            ui.label(format!(
+                "{}{} ({})",
+                tr("Closing Balance: "),
+                format_number(closing_balance.value as f64, 2),
+                closing_balance.date
+            ));
+        } else {
+            ui.label(tr(
+                "No closing balance; balances continue from the last recorded balance.",
+            ));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{Datelike, NaiveDate};
+use egui::Ui;
+use uuid::Uuid;
+
+use crate::{
+    format::{format_amount_with_decimals, parse_number},
+    i18n::{locale, tr, Locale},
+};
+
+/// Edits a date as separate year, month and day fields.
+/// Returns true if the date was changed.
+pub fn date_edit(ui: &mut Ui, date: &mut NaiveDate) -> bool {
+    let mut year = date.year();
+    let mut month = date.month();
+    let mut day = date.day();
+    let mut modified = false;
+
+    ui.horizontal(|ui| {
+        modified |= ui
+            .add(egui::DragValue::new(&mut year).range(1900..=2200))
+            .changed();
+        modified |= ui
+            .add(egui::DragValue::new(&mut month).range(1..=12))
+            .changed();
+        modified |= ui
+            .add(egui::DragValue::new(&mut day).range(1..=31))
+            .changed();
+    });
+
+    if modified {
+        // Clamp the day to the length of the month, e.g. 31 February becomes 28/29 February.
+        if let Some(new_date) = (1..=day)
+            .rev()
+            .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        {
+            *date = new_date;
+        }
+    }
+
+    modified
+}
+
+/// A `DragValue` that shows and reads numbers with the locale's decimal mark.
+pub fn number_drag_value<Num: egui::emath::Numeric>(value: &mut Num) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .custom_formatter(|number, decimals| {
+            let text = egui::emath::format_with_decimals_in_range(number, decimals);
+            match locale() {
+                Locale::English => text,
+                Locale::Swedish => text.replace('.', ","),
+            }
+        })
+        .custom_parser(parse_number)
+}
+
+/// A `DragValue` for an amount of money, shown with thousands separators and the currency.
+pub fn amount_drag_value<'a>(value: &'a mut f32, currency: &str) -> egui::DragValue<'a> {
+    let currency = currency.to_owned();
+    egui::DragValue::new(value)
+        .custom_formatter(move |amount, _| {
+            let decimals = if amount.fract() == 0.0 { 0 } else { 2 };
+            format_amount_with_decimals(amount as f32, &currency, decimals)
+        })
+        .custom_parser(parse_number)
+}
+
+/// Selects which of the given cash assets receives an asset's payouts.
+/// Returns true if the target was changed.
+pub fn payout_target_edit(
+    ui: &mut Ui,
+    id_salt: impl std::hash::Hash,
+    target: &mut Option<Uuid>,
+    cash_assets: &[(Uuid, String)],
+) -> bool {
+    let mut modified = false;
+    let selected_text = cash_assets
+        .iter()
+        .find(|(uuid, _)| Some(*uuid) == *target)
+        .map_or(tr("None").to_owned(), |(_, name)| name.clone());
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Pay out to: "));
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                modified |= ui.selectable_value(target, None, tr("None")).changed();
+                for (uuid, name) in cash_assets {
+                    modified |= ui.selectable_value(target, Some(*uuid), name).changed();
+                }
+            });
+    });
+
+    modified
+}
+
+/// Edits the group of an asset: type a new group or pick one of the existing groups.
+/// Returns true if the group was changed.
+pub fn group_edit(
+    ui: &mut Ui,
+    id_salt: impl std::hash::Hash,
+    group: &mut String,
+    groups: &[String],
+) -> bool {
+    let mut modified = false;
+    let id = ui.make_persistent_id(id_salt);
+    let text_id = id.with("text");
+    // The group is typed into a draft and set on Enter or when the field loses focus, since
+    // changing it moves the asset to another group.
+    let mut draft: String = ui.data_mut(|data| data.get_temp(id).unwrap_or_else(|| group.clone()));
+
+    ui.horizontal(|ui| {
+        ui.label(tr("Group: "));
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut draft)
+                .id(text_id)
+                .desired_width(120.0),
+        );
+        if response.has_focus() {
+            ui.data_mut(|data| data.insert_temp(id, draft.clone()));
+        } else {
+            ui.data_mut(|data| data.remove::<String>(id));
+        }
+        if response.lost_focus() && draft.trim() != group.as_str() {
+            *group = draft.trim().to_owned();
+            modified = true;
+        }
+        if !groups.is_empty() {
+            egui::ComboBox::from_id_salt(id.with("groups"))
+                .selected_text("")
+                .width(20.0)
+                .show_ui(ui, |ui| {
+                    modified |= ui
+                        .selectable_value(group, String::new(), tr("None"))
+                        .changed();
+                    for known in groups {
+                        modified |= ui.selectable_value(group, known.clone(), known).changed();
+                    }
+                });
+        }
+    });
+
+    modified
+}
+
+/// Edits a list of tags: each tag can be removed and new ones are typed in and added with
+/// Enter. Returns true if the tags were changed.
+pub fn tags_edit(ui: &mut Ui, id_salt: impl std::hash::Hash, tags: &mut Vec<String>) -> bool {
+    let mut modified = false;
+    let draft_id = ui.make_persistent_id(id_salt);
+    let mut draft: String = ui.data_mut(|data| data.get_temp(draft_id).unwrap_or_default());
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(tr("Tags: "));
+        let mut index_to_remove = None;
+        for (index, tag) in tags.iter().enumerate() {
+            let chip = format!("{} {}", tag, egui_material_icons::icons::ICON_CLOSE);
+            if ui.small_button(chip).clicked() {
+                index_to_remove = Some(index);
+            }
+        }
+        if let Some(index) = index_to_remove {
+            tags.remove(index);
+            modified = true;
+        }
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut draft)
+                .hint_text(tr("New tag"))
+                .desired_width(80.0),
+        );
+        let tag = draft.trim().to_owned();
+        if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+                modified = true;
+            }
+            draft.clear();
+        }
+    });
+
+    ui.data_mut(|data| data.insert_temp(draft_id, draft));
+    modified
+}
+
+/// The contents of the first file dropped onto the window this frame, if any. The dropped
+/// files are taken from the input, so a drop is only handled once.
+pub fn dropped_file_text(ctx: &egui::Context) -> Option<String> {
+    let dropped_files = ctx.input_mut(|input| std::mem::take(&mut input.raw.dropped_files));
+    dropped_files
+        .first()
+        .and_then(|file| match (&file.bytes, &file.path) {
+            (Some(bytes), _) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            (None, Some(path)) => std::fs::read_to_string(path).ok(),
+            (None, None) => None,
+        })
+}